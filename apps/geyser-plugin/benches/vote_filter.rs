@@ -0,0 +1,50 @@
+//! Per-transaction cost of the vote-skip path in `notify_transaction`.
+//!
+//! Run with `cargo bench -p geyser-plugin --bench vote_filter`.
+
+use agave_geyser_plugin_interface::geyser_plugin_interface::{
+    ReplicaTransactionInfoV3,
+    ReplicaTransactionInfoVersions,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::TransactionStatusMeta;
+
+fn bench_vote_skip(c: &mut Criterion) {
+    let config_path = std::env::temp_dir().join("raywatch_bench_vote_filter.json");
+    std::fs::write(&config_path, r#"{ "skip_votes": true }"#).expect("write bench config");
+
+    // SAFETY: `_create_plugin` hands over ownership of a freshly boxed plugin.
+    let mut plugin = unsafe { Box::from_raw(geyser_plugin::_create_plugin()) };
+    plugin
+        .on_load(config_path.to_str().unwrap(), false)
+        .expect("load plugin");
+
+    let signature = Signature::default();
+    let message_hash = Hash::default();
+    let transaction = VersionedTransaction::default();
+    let meta = TransactionStatusMeta::default();
+    let info = ReplicaTransactionInfoV3 {
+        signature: &signature,
+        message_hash: &message_hash,
+        is_vote: true,
+        transaction: &transaction,
+        transaction_status_meta: &meta,
+        index: 0,
+    };
+
+    c.bench_function("notify_transaction/vote_skipped", |b| {
+        b.iter(|| {
+            plugin
+                .notify_transaction(black_box(ReplicaTransactionInfoVersions::V0_0_3(&info)), 1)
+                .unwrap()
+        })
+    });
+
+    plugin.on_unload();
+}
+
+criterion_group!(benches, bench_vote_skip);
+criterion_main!(benches);
@@ -0,0 +1,238 @@
+use log::warn;
+use serde::Deserialize;
+use std::collections::hash_map::RandomState;
+use std::fmt::Display;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum BackoffStrategy {
+    Fixed,
+    Linear,
+    Exponential,
+    /// Exponential with "full jitter": a uniform delay in `[0, exponential]`,
+    /// so many validators retrying against one broker don't retry in lockstep.
+    #[default]
+    ExponentialJitter,
+}
+
+/// Retry delay policy shared by load-time initialization and send retries.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Backoff {
+    #[serde(default)]
+    pub strategy: BackoffStrategy,
+
+    #[serde(default = "default_base_ms")]
+    pub base_ms: u64,
+
+    #[serde(default = "default_max_ms")]
+    pub max_ms: u64,
+
+    /// Retries after the first attempt; 0 disables retrying.
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+}
+
+fn default_base_ms() -> u64 {
+    100
+}
+
+fn default_max_ms() -> u64 {
+    10_000
+}
+
+fn default_max_retries() -> u32 {
+    5
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            strategy: BackoffStrategy::default(),
+            base_ms: default_base_ms(),
+            max_ms: default_max_ms(),
+            max_retries: default_max_retries(),
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before retry number `attempt` (0-based).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponential = || self.base_ms.saturating_mul(1u64 << attempt.min(32));
+        let ms = match self.strategy {
+            BackoffStrategy::Fixed => self.base_ms,
+            BackoffStrategy::Linear => self.base_ms.saturating_mul(u64::from(attempt) + 1),
+            BackoffStrategy::Exponential => exponential(),
+            BackoffStrategy::ExponentialJitter => {
+                let cap = exponential().min(self.max_ms);
+                random_u64() % cap.saturating_add(1)
+            }
+        };
+        Duration::from_millis(ms.min(self.max_ms))
+    }
+
+    /// The librdkafka settings carrying this policy into broker reconnects
+    /// and protocol request retries. librdkafka always backs off
+    /// exponentially with jitter, so only `fixed` (capped at `base_ms`)
+    /// changes the shape; values are clamped to librdkafka's ranges.
+    pub fn librdkafka_properties(&self) -> [(&'static str, String); 4] {
+        let max_ms = match self.strategy {
+            BackoffStrategy::Fixed => self.base_ms,
+            _ => self.max_ms.max(self.base_ms),
+        };
+        [
+            ("reconnect.backoff.ms", self.base_ms.min(3_600_000).to_string()),
+            ("reconnect.backoff.max.ms", max_ms.min(3_600_000).to_string()),
+            ("retry.backoff.ms", self.base_ms.clamp(1, 300_000).to_string()),
+            ("retry.backoff.max.ms", max_ms.clamp(1, 300_000).to_string()),
+        ]
+    }
+
+    /// Runs `op` until it succeeds or `max_retries` retries are used up,
+    /// sleeping per the strategy in between.
+    #[cfg_attr(not(any(feature = "pulsar", feature = "kinesis")), allow(dead_code))]
+    pub fn retry<T, E: Display>(&self, what: &str, op: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+        self.retry_if(what, op, |_| true)
+    }
+
+    /// Like `retry`, but gives up at once on errors `transient` rejects,
+    /// e.g. invalid settings that no number of retries will fix.
+    pub fn retry_if<T, E: Display>(
+        &self,
+        what: &str,
+        op: impl FnMut() -> Result<T, E>,
+        transient: impl Fn(&E) -> bool,
+    ) -> Result<T, E> {
+        self.run(op, |attempt, e, delay| {
+            if !transient(e) {
+                return false;
+            }
+            warn!(
+                "RaywatchGeyserPlugin: {what} failed (attempt {}): {e}; retrying in {delay:?}",
+                attempt + 1
+            );
+            true
+        })
+    }
+
+    /// Like `retry`, but reports each failed attempt to `on_retry` (with the
+    /// 0-based attempt and the upcoming delay) instead of logging it; it
+    /// returns false to give up early.
+    pub fn retry_with<T, E>(
+        &self,
+        op: impl FnMut() -> Result<T, E>,
+        on_retry: impl FnMut(u32, &E, Duration) -> bool,
+    ) -> Result<T, E> {
+        self.run(op, on_retry)
+    }
+
+    /// Retries while attempts remain and `should_retry` agrees.
+    fn run<T, E>(
+        &self,
+        mut op: impl FnMut() -> Result<T, E>,
+        mut should_retry: impl FnMut(u32, &E, Duration) -> bool,
+    ) -> Result<T, E> {
+        let mut attempt = 0;
+        loop {
+            let e = match op() {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+            let delay = self.delay(attempt);
+            if attempt >= self.max_retries || !should_retry(attempt, &e, delay) {
+                return Err(e);
+            }
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+}
+
+/// Cheap per-call randomness from std's randomly keyed hasher; good enough
+/// for spreading out retries.
+fn random_u64() -> u64 {
+    RandomState::new().hash_one(std::time::Instant::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_delay() -> Backoff {
+        Backoff {
+            strategy: BackoffStrategy::Fixed,
+            base_ms: 0,
+            max_ms: 0,
+            max_retries: 3,
+        }
+    }
+
+    #[test]
+    fn retry_if_gives_up_on_permanent_error() {
+        let mut attempts = 0;
+        let result: Result<(), &str> = no_delay().retry_if(
+            "test",
+            || {
+                attempts += 1;
+                Err("permanent")
+            },
+            |e| *e != "permanent",
+        );
+        assert_eq!(result, Err("permanent"));
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn retry_if_retries_transient_error() {
+        let mut attempts = 0;
+        let result: Result<(), &str> = no_delay().retry_if(
+            "test",
+            || {
+                attempts += 1;
+                Err("transient")
+            },
+            |e| *e != "permanent",
+        );
+        assert_eq!(result, Err("transient"));
+        assert_eq!(attempts, 4);
+    }
+
+    #[test]
+    fn librdkafka_properties_follow_policy() {
+        let backoff = Backoff {
+            strategy: BackoffStrategy::Exponential,
+            base_ms: 250,
+            max_ms: 20_000,
+            max_retries: 3,
+        };
+        assert_eq!(
+            backoff.librdkafka_properties(),
+            [
+                ("reconnect.backoff.ms", "250".to_string()),
+                ("reconnect.backoff.max.ms", "20000".to_string()),
+                ("retry.backoff.ms", "250".to_string()),
+                ("retry.backoff.max.ms", "20000".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn fixed_strategy_caps_librdkafka_backoff_at_base() {
+        let backoff = Backoff {
+            strategy: BackoffStrategy::Fixed,
+            base_ms: 0,
+            ..no_delay()
+        };
+        assert_eq!(
+            backoff.librdkafka_properties(),
+            [
+                ("reconnect.backoff.ms", "0".to_string()),
+                ("reconnect.backoff.max.ms", "0".to_string()),
+                ("retry.backoff.ms", "1".to_string()),
+                ("retry.backoff.max.ms", "1".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use log::info;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Tracks whether the validator is still replaying old blocks after load.
+///
+/// Geyser has no catch-up indicator for transactions (`is_startup` only
+/// covers account updates restored from the snapshot), so this goes by
+/// block time: the first block whose `block_time` is within `max_lag_secs`
+/// of the wall clock ends catch-up for the rest of the session. Blocks may
+/// lack a `block_time`, so catch-up also ends `max_duration` after load.
+pub(crate) struct CatchUp {
+    max_lag_secs: u64,
+    max_duration: Duration,
+    started_at: Instant,
+    caught_up: AtomicBool,
+    backfilled: AtomicU64,
+}
+
+impl CatchUp {
+    /// When `enabled` is false the validator is treated as caught up from
+    /// the start.
+    pub fn new(enabled: bool, max_lag_secs: u64, max_duration: Duration) -> Self {
+        Self {
+            max_lag_secs,
+            max_duration,
+            started_at: Instant::now(),
+            caught_up: AtomicBool::new(!enabled),
+            backfilled: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_caught_up(&self) -> bool {
+        if self.caught_up.load(Ordering::Relaxed) {
+            return true;
+        }
+        if self.started_at.elapsed() < self.max_duration {
+            return false;
+        }
+        self.finish(format_args!(
+            "after {}s without a recent block time",
+            self.max_duration.as_secs()
+        ));
+        true
+    }
+
+    /// Counts an event emitted (or suppressed) as backfill.
+    pub fn count_backfilled(&self) {
+        self.backfilled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn observe_block(&self, slot: u64, block_time: Option<i64>) {
+        if self.is_caught_up() {
+            return;
+        }
+        let Some(block_time) = block_time else {
+            return;
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let lag = now.saturating_sub(block_time);
+        if lag <= self.max_lag_secs as i64 {
+            self.finish(format_args!("at slot {slot} ({lag}s behind wall clock)"));
+        }
+    }
+
+    /// Ends catch-up, logging `reason` if this call is the one that ended it.
+    fn finish(&self, reason: fmt::Arguments<'_>) {
+        if !self.caught_up.swap(true, Ordering::Relaxed) {
+            info!(
+                "RaywatchGeyserPlugin: caught up {reason} after {} backfill events; switching to the live topic",
+                self.backfilled.load(Ordering::Relaxed)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn now_secs() -> i64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64
+    }
+
+    #[test]
+    fn disabled_is_caught_up() {
+        assert!(CatchUp::new(false, 60, HOUR).is_caught_up());
+    }
+
+    #[test]
+    fn recent_block_ends_catch_up() {
+        let catch_up = CatchUp::new(true, 60, HOUR);
+        catch_up.observe_block(1, Some(now_secs() - 3600));
+        assert!(!catch_up.is_caught_up());
+        catch_up.observe_block(2, Some(now_secs() - 10));
+        assert!(catch_up.is_caught_up());
+    }
+
+    #[test]
+    fn missing_block_time_does_not_end_catch_up() {
+        let catch_up = CatchUp::new(true, 60, HOUR);
+        catch_up.observe_block(1, None);
+        assert!(!catch_up.is_caught_up());
+    }
+
+    #[test]
+    fn max_duration_ends_catch_up() {
+        let catch_up = CatchUp::new(true, 60, Duration::ZERO);
+        catch_up.observe_block(1, None);
+        assert!(catch_up.is_caught_up());
+    }
+}
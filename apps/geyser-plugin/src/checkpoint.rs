@@ -0,0 +1,243 @@
+use crate::dedup::RecentSignatures;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// On-disk progress marker: the highest slot a sink accepted an event for,
+/// the number of events accepted so far, the next per-topic offsets and the
+/// signatures in the dedup window.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Checkpoint {
+    pub last_slot: u64,
+    pub seq: u64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub offsets: BTreeMap<String, u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub recent_signatures: BTreeMap<u64, Vec<String>>,
+    pub updated_at_ms: u64,
+}
+
+/// Producer-assigned offsets counted separately for each topic.
+#[derive(Default)]
+pub(crate) struct TopicOffsets(Mutex<BTreeMap<String, u64>>);
+
+impl TopicOffsets {
+    /// Takes `topic`'s next offset, starting from 0. The lock is only held
+    /// for the increment, never while the event is sent.
+    pub fn take(&self, topic: &str) -> u64 {
+        let mut offsets = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match offsets.get_mut(topic) {
+            Some(next) => {
+                *next += 1;
+                *next - 1
+            }
+            None => {
+                offsets.insert(topic.to_string(), 1);
+                0
+            }
+        }
+    }
+
+    /// Returns an `offset` whose event was not delivered, so the next event
+    /// reuses it. Fails, leaving a gap, when a later offset was already
+    /// taken.
+    pub fn give_back(&self, topic: &str, offset: u64) -> bool {
+        let mut offsets = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match offsets.get_mut(topic) {
+            Some(next) if *next == offset + 1 => {
+                *next = offset;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn snapshot(&self) -> BTreeMap<String, u64> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn restore(&self, offsets: BTreeMap<String, u64>) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = offsets;
+    }
+}
+
+struct Progress {
+    last_slot: AtomicU64,
+    seq: AtomicU64,
+    offsets: Arc<TopicOffsets>,
+    recent_signatures: Arc<RecentSignatures>,
+}
+
+impl Progress {
+    fn snapshot(&self) -> Checkpoint {
+        Checkpoint {
+            last_slot: self.last_slot.load(Ordering::Relaxed),
+            seq: self.seq.load(Ordering::Relaxed),
+            offsets: self.offsets.snapshot(),
+            recent_signatures: self.recent_signatures.snapshot(),
+            updated_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Tracks emission progress and periodically persists it to `path` from a
+/// background thread, plus once more on shutdown.
+pub(crate) struct Checkpointer {
+    path: PathBuf,
+    progress: Arc<Progress>,
+    stop: Option<Sender<()>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl Checkpointer {
+    /// Resumes `seq`, `offsets` and `recent_signatures` from the checkpoint
+    /// at `path`, if any; the latter two are persisted along with the rest
+    /// of the progress.
+    pub fn start(
+        path: &str,
+        interval: Duration,
+        offsets: Arc<TopicOffsets>,
+        recent_signatures: Arc<RecentSignatures>,
+    ) -> Self {
+        let path = PathBuf::from(path);
+        let progress = Arc::new(Progress {
+            last_slot: AtomicU64::new(0),
+            seq: AtomicU64::new(0),
+            offsets,
+            recent_signatures,
+        });
+
+        match load(&path) {
+            Ok(Some(previous)) => {
+                info!(
+                    "RaywatchGeyserPlugin: previous checkpoint at {}: last_slot={} seq={} updated_at_ms={}",
+                    path.display(),
+                    previous.last_slot,
+                    previous.seq,
+                    previous.updated_at_ms
+                );
+                progress.seq.store(previous.seq, Ordering::Relaxed);
+                progress.offsets.restore(previous.offsets);
+                progress.recent_signatures.restore(previous.recent_signatures);
+            }
+            Ok(None) => info!("RaywatchGeyserPlugin: no previous checkpoint at {}", path.display()),
+            Err(e) => error!(
+                "RaywatchGeyserPlugin: failed to read checkpoint {}: {e}",
+                path.display()
+            ),
+        }
+
+        let (stop, stop_rx) = mpsc::channel::<()>();
+        let writer = {
+            let path = path.clone();
+            let progress = Arc::clone(&progress);
+            std::thread::Builder::new()
+                .name("raywatchCkpt".to_string())
+                .spawn(move || {
+                    // Any message or a dropped sender means shutdown.
+                    while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                        write(&path, &progress.snapshot());
+                    }
+                })
+                .map_err(|e| error!("RaywatchGeyserPlugin: failed to start checkpoint writer: {e}"))
+                .ok()
+        };
+
+        Self {
+            path,
+            progress,
+            stop: Some(stop),
+            writer,
+        }
+    }
+
+    /// Records that an event for `slot` was accepted by at least one sink.
+    pub fn record(&self, slot: u64) {
+        self.progress.seq.fetch_add(1, Ordering::Relaxed);
+        self.progress.last_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    /// Stops the background writer and persists the final position.
+    pub fn finish(&mut self) {
+        drop(self.stop.take());
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+        let checkpoint = self.progress.snapshot();
+        write(&self.path, &checkpoint);
+        info!(
+            "RaywatchGeyserPlugin: wrote final checkpoint last_slot={} seq={}",
+            checkpoint.last_slot, checkpoint.seq
+        );
+    }
+}
+
+fn load(path: &Path) -> anyhow::Result<Option<Checkpoint>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Writes via a temp file and rename so readers never see a partial file.
+fn write(path: &Path, checkpoint: &Checkpoint) {
+    let result = serde_json::to_vec(checkpoint)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| {
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, bytes)?;
+            std::fs::rename(&tmp, path)?;
+            Ok(())
+        });
+    if let Err(e) = result {
+        error!("RaywatchGeyserPlugin: failed to write checkpoint {}: {e}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_count_per_topic() {
+        let offsets = TopicOffsets::default();
+        assert_eq!(offsets.take("a"), 0);
+        assert_eq!(offsets.take("a"), 1);
+        assert_eq!(offsets.take("b"), 0);
+        assert_eq!(offsets.snapshot(), BTreeMap::from([("a".to_string(), 2), ("b".to_string(), 1)]));
+    }
+
+    #[test]
+    fn latest_offset_can_be_given_back() {
+        let offsets = TopicOffsets::default();
+        let offset = offsets.take("a");
+        assert!(offsets.give_back("a", offset));
+        assert_eq!(offsets.take("a"), offset);
+    }
+
+    #[test]
+    fn offset_before_a_later_one_stays_a_gap() {
+        let offsets = TopicOffsets::default();
+        let failed = offsets.take("a");
+        offsets.take("a");
+        assert!(!offsets.give_back("a", failed));
+        assert_eq!(offsets.take("a"), 2);
+    }
+
+    #[test]
+    fn restored_offsets_continue() {
+        let offsets = TopicOffsets::default();
+        offsets.restore(BTreeMap::from([("a".to_string(), 41)]));
+        assert_eq!(offsets.take("a"), 41);
+    }
+}
@@ -0,0 +1,39 @@
+use flate2::write::GzEncoder;
+use serde::Deserialize;
+use std::io::{self, Write};
+
+/// Compression applied to each serialized event before it reaches the
+/// sinks, independent of any transport compression (e.g. Kafka's
+/// `compression.type`). Compressed payloads carry a `content-encoding`
+/// header naming the codec.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PayloadCompression {
+    #[default]
+    None,
+    Zstd,
+    Gzip,
+}
+
+impl PayloadCompression {
+    /// Value of the `content-encoding` header, `None` when uncompressed.
+    pub fn content_encoding(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Zstd => Some("zstd"),
+            Self::Gzip => Some("gzip"),
+        }
+    }
+
+    pub fn compress(self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(payload.to_vec()),
+            Self::Zstd => zstd::bulk::compress(payload, zstd::DEFAULT_COMPRESSION_LEVEL),
+            Self::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(payload)?;
+                encoder.finish()
+            }
+        }
+    }
+}
@@ -0,0 +1,684 @@
+use crate::backoff::Backoff;
+use crate::compression::PayloadCompression;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct PluginConfig {
+    #[serde(default = "default_kafka_brokers")]
+    pub kafka_brokers: String,
+
+    /// librdkafka `socket.keepalive.enable`; unset keeps the librdkafka default.
+    #[serde(default)]
+    pub socket_keepalive_enable: Option<bool>,
+
+    /// librdkafka `socket.timeout.ms` (10..=300000); unset keeps the default.
+    #[serde(default)]
+    pub socket_timeout_ms: Option<u32>,
+
+    /// librdkafka `socket.connection.setup.timeout.ms` (1000..=2147483647);
+    /// unset keeps the default.
+    #[serde(default)]
+    pub socket_connection_setup_timeout_ms: Option<u32>,
+
+    /// Further Kafka clusters to produce every event to, each with its own
+    /// producer. Sends still run one cluster after another on the
+    /// notification thread, so a stalled cluster delays the rest; set
+    /// `multi_sink_mode = "parallel"` to isolate them.
+    #[serde(default)]
+    pub kafka_clusters: Vec<KafkaClusterConfig>,
+
+    /// Attach the ordered list of invoked program ids to every `TxEvent`.
+    #[serde(default)]
+    pub include_program_sequence: bool,
+
+    /// Upper bound on `program_sequence` length, to keep payloads bounded.
+    #[serde(default = "default_max_program_sequence_len")]
+    pub max_program_sequence_len: usize,
+
+    /// Attach the transaction's program log messages to every `TxEvent`.
+    #[serde(default)]
+    pub include_logs: bool,
+
+    /// Truncate each log message to this many characters; unlimited when unset.
+    #[serde(default)]
+    pub max_log_chars: Option<usize>,
+
+    /// Attach the blockhash the transaction was signed against to every
+    /// `TxEvent`, for estimating how stale it was when landed.
+    #[serde(default)]
+    pub include_recent_blockhash: bool,
+
+    /// Attach the ComputeBudget `SetComputeUnitPrice` value to every
+    /// `TxEvent` as `compute_unit_price` (null when the transaction sets none).
+    #[serde(default)]
+    pub include_priority_fee: bool,
+
+    /// Attach pre/post lamport balances of every write-locked account to
+    /// each `TxEvent`. Costs an entry per writable account, which adds up on
+    /// account-heavy (e.g. routed) transactions; see `max_lamport_deltas`.
+    #[serde(default)]
+    pub include_lamport_deltas: bool,
+
+    /// Upper bound on `lamport_deltas` length, to keep payloads bounded.
+    #[serde(default = "default_max_lamport_deltas")]
+    pub max_lamport_deltas: usize,
+
+    /// Attach the number of signatures to every `TxEvent`; above 1 flags
+    /// multisig or otherwise unusual transactions.
+    #[serde(default)]
+    pub include_signature_count: bool,
+
+    /// Attach the number of accounts the transaction references, including
+    /// ones loaded from lookup tables, to every `TxEvent`.
+    #[serde(default)]
+    pub include_account_count: bool,
+
+    /// Attach the number of invoked instructions, top-level plus inner, to
+    /// every `TxEvent`.
+    #[serde(default)]
+    pub include_instruction_count: bool,
+
+    /// Attach SPL Memo text (e.g. order ids) to every `TxEvent` as `memo`,
+    /// null when the transaction has none.
+    #[serde(default)]
+    pub include_memo: bool,
+
+    /// Truncate `memo` to this many characters.
+    #[serde(default = "default_max_memo_chars")]
+    pub max_memo_chars: usize,
+
+    /// Attach the structured `TransactionError` of failed transactions to
+    /// every `TxEvent` as `error_detail` (null on success), e.g.
+    /// `{"InstructionError": [2, {"Custom": 6001}]}`.
+    #[serde(default)]
+    pub include_error_detail: bool,
+
+    /// Attach `touched_raydium` to every `TxEvent`: whether the transaction
+    /// invoked any of `raydium_programs`, as a cheap routing hint.
+    #[serde(default)]
+    pub include_touched_raydium: bool,
+
+    /// Publish a `SlotDigestEvent` per rooted slot, an order-independent
+    /// digest of the slot's `TxEvent` signatures so consumers can detect
+    /// missing events.
+    #[serde(default)]
+    pub emit_slot_digest: bool,
+
+    /// Ask the validator for entry notifications. When false, `notify_entry`
+    /// is never called and entry handling is skipped entirely.
+    #[serde(default = "default_true")]
+    pub enable_entry_notifications: bool,
+
+    /// Publish `EntryEvent`s. When false, entries are still received (and
+    /// still drive slot tracking such as warmup) but are not sent downstream.
+    #[serde(default = "default_true")]
+    pub emit_entry_events: bool,
+
+    /// Record key for `EntryEvent`s.
+    #[serde(default)]
+    pub entry_key_format: EntryKeyFormat,
+
+    /// `EntryEvent` fields to publish, out of `idx`, `num_hashes` and
+    /// `executed_tx_count`; empty publishes all. `slot` is always included.
+    #[serde(default)]
+    pub entry_fields: Vec<String>,
+
+    /// Publish a `RewardEvent` per reward in each block's metadata to
+    /// `reward_topic`.
+    #[serde(default)]
+    pub emit_rewards: bool,
+
+    #[serde(default = "default_reward_topic")]
+    pub reward_topic: String,
+
+    /// Silences the load-time warning about different event types sharing
+    /// a topic.
+    #[serde(default)]
+    pub allow_shared_topics: bool,
+
+    /// Publish a `StatsEvent` summarizing librdkafka's producer statistics
+    /// to `stats_topic` every `stats_interval_ms`, for deployments without a
+    /// metrics scraper.
+    #[serde(default)]
+    pub emit_stats_events: bool,
+
+    #[serde(default = "default_stats_topic")]
+    pub stats_topic: String,
+
+    #[serde(default = "default_stats_interval_ms")]
+    pub stats_interval_ms: u64,
+
+    /// Also send the `TxEvent` of every transaction invoking more than this
+    /// many instructions (top-level plus inner), e.g. multi-hop arbitrage,
+    /// to `complex_tx_topic`, always with its full `program_sequence` and
+    /// `instruction_count`. Unset disables the complex stream.
+    #[serde(default)]
+    pub complex_tx_threshold: Option<usize>,
+
+    #[serde(default = "default_complex_tx_topic")]
+    pub complex_tx_topic: String,
+
+    /// Program ids (base58) treated as Raydium. Empty means the built-in set
+    /// of Raydium programs.
+    #[serde(default)]
+    pub raydium_programs: Vec<String>,
+
+    /// Emit a `TransferEvent` for each SPL Token `transfer`/`transferChecked`
+    /// in transactions that invoke a Raydium program.
+    #[serde(default)]
+    pub emit_token_transfers: bool,
+
+    /// Debugging aid for writing decoders: log a hex dump of the data of
+    /// every instruction invoking one of `raydium_programs`, since none are
+    /// decoded. Logged at debug level, so it also needs `RUST_LOG=debug`.
+    /// At most one transaction is dumped per `hexdump_interval_ms`. Leave
+    /// off in production.
+    #[serde(default)]
+    pub hexdump_unknown_instructions: bool,
+
+    #[serde(default = "default_hexdump_interval_ms")]
+    pub hexdump_interval_ms: u64,
+
+    /// Number of slots after load during which no events are emitted.
+    #[serde(default)]
+    pub warmup_slots: u64,
+
+    /// Treat events from blocks replayed while the validator catches up
+    /// after load as backfill: route them to `backfill_topic`, or drop them
+    /// when that is unset (with a throttled warning). Catch-up ends at the
+    /// first block whose `block_time` is within `backfill_max_lag_secs` of
+    /// the wall clock, or `backfill_max_secs` after load at the latest.
+    #[serde(default)]
+    pub separate_backfill: bool,
+
+    #[serde(default)]
+    pub backfill_topic: Option<String>,
+
+    #[serde(default = "default_backfill_max_lag_secs")]
+    pub backfill_max_lag_secs: u64,
+
+    /// Longest catch-up may last, in case no block carries a recent
+    /// `block_time`.
+    #[serde(default = "default_backfill_max_secs")]
+    pub backfill_max_secs: u64,
+
+    /// Only emit events for slots in these epochs (`slot / 432000`), for
+    /// epoch-aligned backfills; empty means every epoch.
+    #[serde(default)]
+    pub emit_epochs: Vec<u64>,
+
+    /// Drop vote transactions on the hot path, before any logging or encoding.
+    #[serde(default)]
+    pub skip_votes: bool,
+
+    /// Skip transactions paying less than this fee, to cut dust/spam; 0
+    /// disables the filter.
+    #[serde(default)]
+    pub min_fee_lamports: u64,
+
+    /// File recording the last slot and count of events accepted by at least
+    /// one sink (transform drops and failed sends are not counted); unset
+    /// disables checkpointing.
+    #[serde(default)]
+    pub checkpoint_path: Option<String>,
+
+    #[serde(default = "default_checkpoint_interval_secs")]
+    pub checkpoint_interval_secs: u64,
+
+    /// Skip transactions whose signature was already emitted within this
+    /// many slots, e.g. when a slot is replayed after a restart. With
+    /// `checkpoint_path` set the window is persisted in the checkpoint, at
+    /// the cost of a larger checkpoint file. 0 disables deduplication.
+    #[serde(default)]
+    pub dedup_window_slots: u64,
+
+    /// Add a per-topic `offset` to every event, starting at 0 and persisted
+    /// in the checkpoint (periodically and on shutdown) so it continues
+    /// across restarts; requires `checkpoint_path`. Offsets are taken
+    /// without holding up concurrent sends, so events may reach the topic
+    /// slightly out of offset order. An undelivered event's offset is
+    /// reused unless a later one was taken meanwhile, in which case it stays
+    /// a gap and a (throttled) warning is logged. After a crash the counter
+    /// rewinds to the last checkpoint, so up to `checkpoint_interval_secs`
+    /// of offsets may be assigned again.
+    #[serde(default)]
+    pub emit_topic_offsets: bool,
+
+    /// Stamp every event with `ingest_time_ms` (wall clock), `ingest_mono_ns`
+    /// (monotonic nanoseconds since load) and `mono_anchor_unix_ns` (the
+    /// wall-clock time of that load, in nanoseconds) headers. Differences of
+    /// `ingest_mono_ns` are exact; `mono_anchor_unix_ns + ingest_mono_ns`
+    /// approximates absolute time. The anchor changes on every load, so only
+    /// compare `ingest_mono_ns` between events sharing an anchor.
+    #[serde(default)]
+    pub include_monotonic_ts: bool,
+
+    /// Compress every payload before handing it to the sinks, for sinks
+    /// without transport compression; see `PayloadCompression`. Kinesis
+    /// records carry no headers, so Kinesis consumers must know the codec.
+    #[serde(default)]
+    pub payload_compression: PayloadCompression,
+
+    /// Create any missing topics through the Kafka AdminClient at load.
+    #[serde(default)]
+    pub auto_create_topics: bool,
+
+    /// Per-topic provisioning settings for `auto_create_topics`, keyed by the
+    /// plugin's topic name (also for topics a cluster renames). Topics without
+    /// an entry are created with `TopicSettings::default()`.
+    #[serde(default)]
+    pub topic_settings: HashMap<String, TopicSettings>,
+
+    /// What to do with `ReplicaTransactionInfo::V0_0_1` notifications, which
+    /// carry no transaction index.
+    #[serde(default)]
+    pub v0_0_1_policy: MissingIndexPolicy,
+
+    /// What to do when an event fails to serialize.
+    #[serde(default)]
+    pub on_serialization_error: SerializationErrorPolicy,
+
+    /// Topic receiving `DeadLetterEvent` diagnostics.
+    #[serde(default = "default_dead_letter_topic")]
+    pub dead_letter_topic: String,
+
+    /// Retry a send a sink rejected, per `backoff`, before giving up on it
+    /// (logged). Requires `multi_sink_mode = "parallel"`: retries sleep on
+    /// the sink's own thread, never on the validator's notification thread.
+    /// Exclusive with `queue_full_retries`, so Kafka sends are retried at one
+    /// layer only.
+    #[serde(default)]
+    pub retry_failed_sends: bool,
+
+    /// Route events a sink still rejects to `dead_letter_topic` as a
+    /// `DeadLetterEvent` carrying the raw payload.
+    #[serde(default)]
+    pub dead_letter_failed_sends: bool,
+
+    /// Sink receiving those dead letters, by log name ("Kafka",
+    /// "Kafka[<cluster>]", "Pulsar", "Kinesis"); unset sends them to the
+    /// sink that failed.
+    #[serde(default)]
+    pub dead_letter_sink: Option<String>,
+
+    /// How events are handed to multiple sinks.
+    #[serde(default)]
+    pub multi_sink_mode: MultiSinkMode,
+
+    /// Per-sink queue length in `parallel` mode; sends to a full queue fail.
+    #[serde(default = "default_sink_queue_capacity")]
+    pub sink_queue_capacity: usize,
+
+    /// Window within which identical send/flush error lines are collapsed
+    /// into one line with a repeat count; 0 logs every occurrence.
+    #[serde(default = "default_log_cooldown_secs")]
+    pub log_cooldown_secs: u64,
+
+    /// How long `on_unload` waits for each sink to deliver outstanding messages.
+    #[serde(default = "default_shutdown_flush_timeout_ms")]
+    pub shutdown_flush_timeout_ms: u64,
+
+    /// Extra attempts for a Kafka send rejected because librdkafka's local
+    /// queue is full, each after a non-blocking poll; 0 drops the event at
+    /// once. Never waits, so a broker outage cannot stall the notification
+    /// thread.
+    #[serde(default)]
+    pub queue_full_retries: u32,
+
+    /// Retry policy for load-time initialization and `retry_failed_sends`.
+    /// Also sets librdkafka's broker reconnect and request retry backoff
+    /// (`reconnect.backoff*.ms`, `retry.backoff*.ms`), unless a cluster's
+    /// `properties` override them.
+    #[serde(default)]
+    pub backoff: Backoff,
+
+    /// WASM module run on every serialized event before it is sent; it may
+    /// rewrite or drop the event. Requires the `wasm-transform` feature.
+    #[serde(default)]
+    pub transform_wasm: Option<String>,
+
+    /// Per-event execution budget for `transform_wasm`, in wasmtime fuel units.
+    #[serde(default = "default_transform_fuel")]
+    pub transform_fuel: u64,
+
+    /// Also produce every event to Apache Pulsar. Requires the `pulsar` feature.
+    #[serde(default)]
+    pub pulsar: Option<PulsarConfig>,
+
+    /// Also put every event to an Amazon Kinesis data stream. Requires the
+    /// `kinesis` feature.
+    #[serde(default)]
+    pub kinesis: Option<KinesisConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SerializationErrorPolicy {
+    /// Drop the event silently.
+    Drop,
+    /// Send a `DeadLetterEvent` describing the failure to `dead_letter_topic`.
+    Dlq,
+    /// Log the error and skip the event.
+    #[default]
+    PanicSafeSkip,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(not(feature = "pulsar"), allow(dead_code))]
+pub(crate) struct PulsarConfig {
+    #[serde(default = "default_pulsar_service_url")]
+    pub service_url: String,
+
+    /// Pulsar topic for the main topic's events.
+    #[serde(default = "default_pulsar_topic")]
+    pub topic: String,
+
+    /// Pulsar topics for the plugin's other topics, from the plugin's topic
+    /// name to Pulsar's; unlisted topics keep their name. Each gets its own
+    /// producer, created on its first event.
+    #[serde(default)]
+    pub topics: HashMap<String, String>,
+
+    #[serde(default = "default_pulsar_producer_name")]
+    pub producer_name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct KafkaClusterConfig {
+    /// Label used in logs.
+    pub name: String,
+
+    pub brokers: String,
+
+    /// Extra librdkafka properties for this cluster (e.g.
+    /// `security.protocol`, `sasl.*`, `linger.ms`), applied last.
+    #[serde(default)]
+    pub properties: HashMap<String, String>,
+
+    /// Topic renames on this cluster, from the plugin's topic name to the
+    /// cluster's; unlisted topics keep their name.
+    #[serde(default)]
+    pub topics: HashMap<String, String>,
+}
+
+/// Credentials come from the standard AWS provider chain (environment,
+/// profile, instance role).
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(not(feature = "kinesis"), allow(dead_code))]
+pub(crate) struct KinesisConfig {
+    /// Stream for the main topic, and for any topic not listed in `streams`.
+    pub stream_name: String,
+
+    /// Streams for other topics, from the plugin's topic name to a stream.
+    /// Records of unlisted topics other than the main one share
+    /// `stream_name`, their partition key prefixed with `{topic}/` so
+    /// consumers can tell them apart.
+    #[serde(default)]
+    pub streams: HashMap<String, String>,
+
+    /// Overrides the region from the provider chain.
+    #[serde(default)]
+    pub region: Option<String>,
+
+    /// Custom endpoint, e.g. LocalStack.
+    #[serde(default)]
+    pub endpoint_url: Option<String>,
+
+    /// Records per `PutRecords` call; Kinesis accepts at most 500.
+    #[serde(default = "default_kinesis_batch_size")]
+    pub batch_size: usize,
+
+    /// Longest a partial batch waits before it is put anyway.
+    #[serde(default = "default_kinesis_linger_ms")]
+    pub linger_ms: u64,
+
+    /// Records buffered or awaiting `PutRecords` before further sends fail
+    /// (and are dead-lettered, if enabled) instead of queueing without bound.
+    #[serde(default = "default_kinesis_max_in_flight")]
+    pub max_in_flight: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum MultiSinkMode {
+    /// Send to each sink in turn on the notification thread.
+    #[default]
+    Sequential,
+    /// Give each sink its own thread and queue, so a slow sink does not
+    /// delay the others. Send errors are then retried (with
+    /// `retry_failed_sends`) and logged by the sink's thread, and bypass
+    /// `dead_letter_failed_sends`, which only sees queue-full rejections.
+    Parallel,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum EntryKeyFormat {
+    /// Slot, 8 bytes big-endian; all entries of a slot share the key.
+    #[default]
+    Slot,
+    /// Slot (8 bytes) then entry index (4 bytes), both big-endian: unique
+    /// per entry and ordered by slot, then index.
+    SlotIndex,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum MissingIndexPolicy {
+    /// Emit the event with `index: null`.
+    #[default]
+    Emit,
+    /// Drop index-less transactions.
+    Skip,
+    /// Emit with `index: null` and log a warning the first time it happens.
+    Warn,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct TopicSettings {
+    #[serde(default = "default_partitions")]
+    pub partitions: i32,
+
+    #[serde(default = "default_replication_factor")]
+    pub replication_factor: i32,
+
+    /// Maps to the topic-level `retention.ms`; broker default when unset.
+    #[serde(default)]
+    pub retention_ms: Option<i64>,
+
+    /// Maps to the topic-level `retention.bytes`; broker default when unset.
+    #[serde(default)]
+    pub retention_bytes: Option<i64>,
+}
+
+pub(crate) fn default_kafka_brokers() -> String {
+    "localhost:9092".to_string()
+}
+
+fn default_max_memo_chars() -> usize {
+    256
+}
+
+fn default_max_lamport_deltas() -> usize {
+    64
+}
+
+fn default_reward_topic() -> String {
+    "raydium-rewards".to_string()
+}
+
+fn default_hexdump_interval_ms() -> u64 {
+    1000
+}
+
+fn default_backfill_max_lag_secs() -> u64 {
+    30
+}
+
+fn default_backfill_max_secs() -> u64 {
+    3600
+}
+
+fn default_sink_queue_capacity() -> usize {
+    10_000
+}
+
+fn default_complex_tx_topic() -> String {
+    "raydium-swaps-complex".to_string()
+}
+
+fn default_stats_topic() -> String {
+    "raydium-swaps-stats".to_string()
+}
+
+fn default_stats_interval_ms() -> u64 {
+    60_000
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_program_sequence_len() -> usize {
+    64
+}
+
+fn default_checkpoint_interval_secs() -> u64 {
+    5
+}
+
+fn default_dead_letter_topic() -> String {
+    "raydium-swaps-dlq".to_string()
+}
+
+fn default_log_cooldown_secs() -> u64 {
+    5
+}
+
+fn default_shutdown_flush_timeout_ms() -> u64 {
+    5_000
+}
+
+fn default_partitions() -> i32 {
+    1
+}
+
+fn default_replication_factor() -> i32 {
+    1
+}
+
+fn default_transform_fuel() -> u64 {
+    10_000_000
+}
+
+fn default_kinesis_batch_size() -> usize {
+    500
+}
+
+fn default_kinesis_linger_ms() -> u64 {
+    100
+}
+
+fn default_kinesis_max_in_flight() -> usize {
+    100_000
+}
+
+fn default_pulsar_service_url() -> String {
+    "pulsar://localhost:6650".to_string()
+}
+
+fn default_pulsar_topic() -> String {
+    "persistent://public/default/raydium-swaps-raw".to_string()
+}
+
+fn default_pulsar_producer_name() -> String {
+    "raywatch-geyser".to_string()
+}
+
+impl Default for PluginConfig {
+    fn default() -> Self {
+        Self {
+            kafka_brokers: default_kafka_brokers(),
+            socket_keepalive_enable: None,
+            socket_timeout_ms: None,
+            socket_connection_setup_timeout_ms: None,
+            kafka_clusters: Vec::new(),
+            include_program_sequence: false,
+            max_program_sequence_len: default_max_program_sequence_len(),
+            include_logs: false,
+            max_log_chars: None,
+            include_recent_blockhash: false,
+            include_priority_fee: false,
+            include_lamport_deltas: false,
+            max_lamport_deltas: default_max_lamport_deltas(),
+            include_signature_count: false,
+            include_account_count: false,
+            include_instruction_count: false,
+            include_memo: false,
+            max_memo_chars: default_max_memo_chars(),
+            include_error_detail: false,
+            include_touched_raydium: false,
+            emit_slot_digest: false,
+            enable_entry_notifications: true,
+            emit_entry_events: true,
+            entry_key_format: EntryKeyFormat::default(),
+            entry_fields: Vec::new(),
+            emit_rewards: false,
+            reward_topic: default_reward_topic(),
+            allow_shared_topics: false,
+            emit_stats_events: false,
+            stats_topic: default_stats_topic(),
+            stats_interval_ms: default_stats_interval_ms(),
+            complex_tx_threshold: None,
+            complex_tx_topic: default_complex_tx_topic(),
+            raydium_programs: Vec::new(),
+            emit_token_transfers: false,
+            hexdump_unknown_instructions: false,
+            hexdump_interval_ms: default_hexdump_interval_ms(),
+            warmup_slots: 0,
+            separate_backfill: false,
+            backfill_topic: None,
+            backfill_max_lag_secs: default_backfill_max_lag_secs(),
+            backfill_max_secs: default_backfill_max_secs(),
+            emit_epochs: Vec::new(),
+            skip_votes: false,
+            min_fee_lamports: 0,
+            checkpoint_path: None,
+            checkpoint_interval_secs: default_checkpoint_interval_secs(),
+            dedup_window_slots: 0,
+            emit_topic_offsets: false,
+            include_monotonic_ts: false,
+            payload_compression: PayloadCompression::default(),
+            auto_create_topics: false,
+            topic_settings: HashMap::new(),
+            v0_0_1_policy: MissingIndexPolicy::default(),
+            on_serialization_error: SerializationErrorPolicy::default(),
+            dead_letter_topic: default_dead_letter_topic(),
+            retry_failed_sends: false,
+            dead_letter_failed_sends: false,
+            dead_letter_sink: None,
+            multi_sink_mode: MultiSinkMode::default(),
+            sink_queue_capacity: default_sink_queue_capacity(),
+            log_cooldown_secs: default_log_cooldown_secs(),
+            shutdown_flush_timeout_ms: default_shutdown_flush_timeout_ms(),
+            queue_full_retries: 0,
+            backoff: Backoff::default(),
+            transform_wasm: None,
+            transform_fuel: default_transform_fuel(),
+            pulsar: None,
+            kinesis: None,
+        }
+    }
+}
+
+impl Default for TopicSettings {
+    fn default() -> Self {
+        Self {
+            partitions: default_partitions(),
+            replication_factor: default_replication_factor(),
+            retention_ms: None,
+            retention_bytes: None,
+        }
+    }
+}
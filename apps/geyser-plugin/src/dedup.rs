@@ -0,0 +1,167 @@
+use solana_sdk::signature::Signature;
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct Recent {
+    by_slot: BTreeMap<u64, Vec<Signature>>,
+    seen: HashSet<Signature>,
+}
+
+/// Transaction signatures emitted within the last `window_slots` slots
+/// (counted back from the highest slot seen), so a slot replayed after a
+/// restart or reorg is not emitted twice. Snapshotted into the checkpoint
+/// so the window survives restarts.
+pub(crate) struct RecentSignatures {
+    window_slots: u64,
+    recent: Mutex<Recent>,
+    hits: AtomicU64,
+}
+
+impl RecentSignatures {
+    /// A zero `window_slots` disables deduplication.
+    pub fn new(window_slots: u64) -> Self {
+        Self {
+            window_slots,
+            recent: Mutex::new(Recent::default()),
+            hits: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.window_slots > 0
+    }
+
+    /// Whether `signature` was emitted within the window, counting a hit if so.
+    pub fn is_duplicate(&self, signature: &Signature) -> bool {
+        let recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        let duplicate = recent.seen.contains(signature);
+        if duplicate {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        duplicate
+    }
+
+    /// Records `signature` as emitted in `slot`.
+    pub fn insert(&self, slot: u64, signature: &Signature) {
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.seen.insert(*signature) {
+            recent.by_slot.entry(slot).or_default().push(*signature);
+            self.evict(&mut recent);
+        }
+    }
+
+    /// Duplicates skipped since load.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn snapshot(&self) -> BTreeMap<u64, Vec<String>> {
+        // Copy the raw signatures and release the lock before the (much
+        // slower) base58 encoding, so notifications are not held up.
+        let by_slot = self.recent.lock().unwrap_or_else(|e| e.into_inner()).by_slot.clone();
+        by_slot
+            .into_iter()
+            .map(|(slot, signatures)| (slot, signatures.iter().map(|s| s.to_string()).collect()))
+            .collect()
+    }
+
+    pub(crate) fn restore(&self, snapshot: BTreeMap<u64, Vec<String>>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        for (slot, signatures) in snapshot {
+            let signatures: Vec<Signature> = signatures
+                .iter()
+                .filter_map(|signature| Signature::from_str(signature).ok())
+                .collect();
+            recent.seen.extend(signatures.iter().copied());
+            recent.by_slot.entry(slot).or_default().extend(signatures);
+        }
+        self.evict(&mut recent);
+    }
+
+    fn evict(&self, recent: &mut Recent) {
+        let Some(&highest) = recent.by_slot.keys().next_back() else {
+            return;
+        };
+        let oldest_kept = highest.saturating_sub(self.window_slots.saturating_sub(1));
+        while let Some(entry) = recent.by_slot.first_entry() {
+            if *entry.key() >= oldest_kept {
+                break;
+            }
+            for signature in entry.remove() {
+                recent.seen.remove(&signature);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(n: u8) -> Signature {
+        Signature::from([n; 64])
+    }
+
+    #[test]
+    fn disabled_with_zero_window() {
+        assert!(!RecentSignatures::new(0).is_enabled());
+    }
+
+    #[test]
+    fn counts_duplicates() {
+        let recent = RecentSignatures::new(10);
+        recent.insert(100, &signature(1));
+        assert!(recent.is_duplicate(&signature(1)));
+        assert!(!recent.is_duplicate(&signature(2)));
+        assert_eq!(recent.hits(), 1);
+    }
+
+    #[test]
+    fn evicts_slots_older_than_window() {
+        let recent = RecentSignatures::new(10);
+        recent.insert(100, &signature(1));
+        recent.insert(105, &signature(2));
+        recent.insert(109, &signature(3));
+        assert!(recent.is_duplicate(&signature(1)));
+
+        // Slot 110 keeps 101..=110.
+        recent.insert(110, &signature(4));
+        assert!(!recent.is_duplicate(&signature(1)));
+        assert!(recent.is_duplicate(&signature(2)));
+        assert!(recent.is_duplicate(&signature(4)));
+    }
+
+    #[test]
+    fn restart_during_replay_suppresses_emitted_signatures() {
+        let before = RecentSignatures::new(10);
+        before.insert(100, &signature(1));
+        before.insert(101, &signature(2));
+        let snapshot = before.snapshot();
+
+        let after = RecentSignatures::new(10);
+        after.restore(snapshot.clone());
+        assert_eq!(after.snapshot(), snapshot);
+        assert!(after.is_duplicate(&signature(1)));
+        assert!(after.is_duplicate(&signature(2)));
+        assert!(!after.is_duplicate(&signature(3)));
+    }
+
+    #[test]
+    fn restore_evicts_beyond_window_and_skips_bad_signatures() {
+        let snapshot = BTreeMap::from([
+            (50, vec![signature(1).to_string()]),
+            (100, vec![signature(2).to_string(), "not-base58!".to_string()]),
+        ]);
+        let recent = RecentSignatures::new(10);
+        recent.restore(snapshot);
+        assert!(!recent.is_duplicate(&signature(1)));
+        assert!(recent.is_duplicate(&signature(2)));
+        assert_eq!(recent.snapshot(), BTreeMap::from([(100, vec![signature(2).to_string()])]));
+    }
+}
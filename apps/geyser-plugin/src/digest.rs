@@ -0,0 +1,167 @@
+//! Per-slot digest over emitted transaction signatures.
+//!
+//! Construction, so consumers can reproduce it: start from 32 zero bytes and,
+//! for each `TxEvent` of the slot, XOR in `sha256(signature)`, where
+//! `signature` is the 64 raw bytes of the base58 `signature` field. XOR makes
+//! the digest independent of the order events arrive in, which varies when
+//! transactions are notified concurrently; a consumer folds in every
+//! `TxEvent` it received, duplicates included. The result is published
+//! base58 encoded as `slot_digest` on the slot's `SlotDigestEvent`, together
+//! with the number of folded events. Only events a sink accepted are
+//! folded in; those a transform dropped are not.
+
+use log::warn;
+use solana_sdk::hash::{hash, Hash};
+use solana_sdk::signature::Signature;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Open slots this far below a newly rooted slot are dropped without a
+/// digest: they were on an abandoned fork, or their root notification was
+/// missed.
+const MAX_SLOT_AGE: u64 = 512;
+
+/// Hard cap on open slots, in case roots stop arriving altogether.
+const MAX_OPEN_SLOTS: usize = 1024;
+
+pub(crate) struct SlotDigest {
+    pub digest: Hash,
+    pub count: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct SlotDigests(Mutex<BTreeMap<u64, SlotDigest>>);
+
+impl SlotDigests {
+    pub fn record(&self, slot: u64, signature: &Signature) {
+        let mut slots = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if slots.len() >= MAX_OPEN_SLOTS
+            && !slots.contains_key(&slot)
+            && let Some((oldest, dropped)) = slots.pop_first()
+        {
+            warn!(
+                "RaywatchGeyserPlugin: {MAX_OPEN_SLOTS} slots awaiting a root; dropping slot {oldest} without a digest ({} transactions)",
+                dropped.count
+            );
+        }
+        let entry = slots.entry(slot).or_insert(SlotDigest {
+            digest: Hash::default(),
+            count: 0,
+        });
+        let mut digest = entry.digest.to_bytes();
+        for (byte, signature_byte) in digest.iter_mut().zip(hash(signature.as_ref()).as_ref()) {
+            *byte ^= signature_byte;
+        }
+        entry.digest = Hash::new_from_array(digest);
+        entry.count += 1;
+    }
+
+    /// Closes `slot` once it is rooted, returning its digest; an empty chain
+    /// when nothing was recorded for it. Slots more than `MAX_SLOT_AGE`
+    /// below it are dropped with a warning.
+    pub fn finish(&self, slot: u64) -> SlotDigest {
+        let mut slots = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let digest = slots.remove(&slot).unwrap_or(SlotDigest {
+            digest: Hash::default(),
+            count: 0,
+        });
+        let oldest_kept = slot.saturating_sub(MAX_SLOT_AGE);
+        while let Some(entry) = slots.first_entry()
+            && *entry.key() < oldest_kept
+        {
+            let (stale, dropped) = entry.remove_entry();
+            warn!(
+                "RaywatchGeyserPlugin: slot {stale} was never rooted; dropping it without a digest ({} transactions)",
+                dropped.count
+            );
+        }
+        digest
+    }
+
+    /// Forgets `slot` after it was marked dead; its transactions never
+    /// reach a rooted block, so no digest is sent.
+    pub fn discard(&self, slot: u64) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).remove(&slot);
+    }
+
+    #[cfg(test)]
+    fn is_open(&self, slot: u64) -> bool {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).contains_key(&slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hasher;
+    use std::str::FromStr;
+
+    /// What a consumer does with the published fields alone.
+    fn recompute(signatures: &[String]) -> String {
+        let mut digest = [0u8; 32];
+        for signature in signatures {
+            let signature = Signature::from_str(signature).unwrap();
+            let mut hasher = Hasher::default();
+            hasher.hash(signature.as_ref());
+            for (byte, signature_byte) in digest.iter_mut().zip(hasher.result().to_bytes()) {
+                *byte ^= signature_byte;
+            }
+        }
+        Hash::new_from_array(digest).to_string()
+    }
+
+    #[test]
+    fn consumer_can_recompute_digest() {
+        let digests = SlotDigests::default();
+        let signatures: Vec<String> = (1..=3u8)
+            .map(|n| {
+                let signature = Signature::from([n; 64]);
+                digests.record(7, &signature);
+                signature.to_string()
+            })
+            .collect();
+
+        let digest = digests.finish(7);
+        assert_eq!(digest.count, 3);
+        assert_eq!(digest.digest.to_string(), recompute(&signatures));
+    }
+
+    #[test]
+    fn digest_ignores_arrival_order() {
+        let forward = SlotDigests::default();
+        let backward = SlotDigests::default();
+        for n in 1..=3u8 {
+            forward.record(7, &Signature::from([n; 64]));
+            backward.record(7, &Signature::from([4 - n; 64]));
+        }
+        assert_eq!(forward.finish(7).digest, backward.finish(7).digest);
+    }
+
+    #[test]
+    fn empty_slot_has_zero_digest() {
+        let digest = SlotDigests::default().finish(7);
+        assert_eq!(digest.count, 0);
+        assert_eq!(digest.digest.to_string(), recompute(&[]));
+    }
+
+    #[test]
+    fn root_evicts_stale_slots() {
+        let digests = SlotDigests::default();
+        let signature = Signature::from([1; 64]);
+        digests.record(10, &signature);
+        digests.record(900, &signature);
+        digests.record(1000, &signature);
+
+        digests.finish(1000);
+        assert!(!digests.is_open(10));
+        assert!(digests.is_open(900));
+    }
+
+    #[test]
+    fn dead_slot_is_discarded() {
+        let digests = SlotDigests::default();
+        digests.record(5, &Signature::from([1; 64]));
+        digests.discard(5);
+        assert!(!digests.is_open(5));
+    }
+}
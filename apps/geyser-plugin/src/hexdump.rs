@@ -0,0 +1,57 @@
+use std::fmt::Write;
+
+const BYTES_PER_LINE: usize = 16;
+
+/// Renders `data` as a classic hex dump, 16 bytes per line:
+///
+/// ```text
+/// 00000000  09 40 42 0f 00 00 00 00  00 e8 03 00 00 00 00 00  |.@B.............|
+/// ```
+pub(crate) fn hexdump(data: &[u8]) -> String {
+    let mut out = String::new();
+    for (line, chunk) in data.chunks(BYTES_PER_LINE).enumerate() {
+        let _ = write!(out, "{:08x} ", line * BYTES_PER_LINE);
+        for i in 0..BYTES_PER_LINE {
+            if i % 8 == 0 {
+                out.push(' ');
+            }
+            match chunk.get(i) {
+                Some(byte) => {
+                    let _ = write!(out, "{byte:02x} ");
+                }
+                None => out.push_str("   "),
+            }
+        }
+        out.push_str(" |");
+        out.extend(chunk.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        }));
+        out.push_str("|\n");
+    }
+    let _ = write!(out, "{:08x}", data.len());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_classic_layout() {
+        let expected = concat!(
+            "00000000  52 61 79 64 69 75 6d 20  73 77 61 70 20 76 34 00  |Raydium swap v4.|\n",
+            "00000010  01 02 ff                                          |...|\n",
+            "00000013",
+        );
+        assert_eq!(hexdump(b"Raydium swap v4\x00\x01\x02\xff"), expected);
+    }
+
+    #[test]
+    fn empty_input_is_just_the_length() {
+        assert_eq!(hexdump(&[]), "00000000");
+    }
+}
@@ -0,0 +1,116 @@
+use crate::config::EntryKeyFormat;
+use std::ops::Deref;
+
+/// Longest key any strategy produces: slot and index.
+const MAX_KEY_LEN: usize = 12;
+
+/// A record key, held inline so deriving one per event does not allocate.
+#[derive(Clone, Copy)]
+pub(crate) struct Key {
+    bytes: [u8; MAX_KEY_LEN],
+    len: usize,
+}
+
+impl Deref for Key {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// What a key strategy may derive a record key from.
+pub(crate) struct KeyContext {
+    pub slot: u64,
+    /// Position of the event within its slot (entry index), when it has one.
+    pub index: Option<usize>,
+}
+
+/// Derives the record key (Kafka partitioning / Pulsar ordering key) for an
+/// event. Chosen once at load so the send path does no config branching.
+pub(crate) trait KeyStrategy: Send + Sync {
+    fn key(&self, ctx: &KeyContext) -> Key;
+}
+
+/// Slot, 8 bytes big-endian.
+pub(crate) struct SlotKey;
+
+impl KeyStrategy for SlotKey {
+    fn key(&self, ctx: &KeyContext) -> Key {
+        let mut bytes = [0; MAX_KEY_LEN];
+        bytes[..8].copy_from_slice(&ctx.slot.to_be_bytes());
+        Key { bytes, len: 8 }
+    }
+}
+
+/// Slot (8 bytes) then index (4 bytes, saturating), both big-endian. Falls
+/// back to index 0 for events without one.
+pub(crate) struct SlotIndexKey;
+
+impl KeyStrategy for SlotIndexKey {
+    fn key(&self, ctx: &KeyContext) -> Key {
+        let index = u32::try_from(ctx.index.unwrap_or(0)).unwrap_or(u32::MAX);
+        let mut bytes = [0; MAX_KEY_LEN];
+        bytes[..8].copy_from_slice(&ctx.slot.to_be_bytes());
+        bytes[8..].copy_from_slice(&index.to_be_bytes());
+        Key { bytes, len: 12 }
+    }
+}
+
+pub(crate) fn for_entries(format: EntryKeyFormat) -> Box<dyn KeyStrategy> {
+    match format {
+        EntryKeyFormat::Slot => Box::new(SlotKey),
+        EntryKeyFormat::SlotIndex => Box::new(SlotIndexKey),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SLOT: u64 = 0x0102_0304_0506_0708;
+
+    fn entry_key(format: EntryKeyFormat, index: usize) -> Key {
+        for_entries(format).key(&KeyContext {
+            slot: SLOT,
+            index: Some(index),
+        })
+    }
+
+    #[test]
+    fn slot_format_is_big_endian_slot() {
+        assert_eq!(entry_key(EntryKeyFormat::Slot, 9)[..], [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn slot_index_format_is_big_endian_slot_then_index() {
+        assert_eq!(
+            entry_key(EntryKeyFormat::SlotIndex, 0x0a0b_0c0d)[..],
+            [1, 2, 3, 4, 5, 6, 7, 8, 0x0a, 0x0b, 0x0c, 0x0d]
+        );
+    }
+
+    #[test]
+    fn slot_key_ignores_index() {
+        let key = SlotKey.key(&KeyContext {
+            slot: SLOT,
+            index: Some(9),
+        });
+        assert_eq!(key[..], SLOT.to_be_bytes());
+    }
+
+    #[test]
+    fn slot_index_key_defaults_missing_index_to_zero() {
+        let key = SlotIndexKey.key(&KeyContext { slot: SLOT, index: None });
+        assert_eq!(key[..], [1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn slot_index_key_saturates_large_index() {
+        let key = SlotIndexKey.key(&KeyContext {
+            slot: SLOT,
+            index: Some(usize::MAX),
+        });
+        assert_eq!(key[..], [1, 2, 3, 4, 5, 6, 7, 8, 0xff, 0xff, 0xff, 0xff]);
+    }
+}
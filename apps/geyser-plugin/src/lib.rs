@@ -1,49 +1,477 @@
-use log::{error, info};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use log::{debug, error, info, log_enabled, trace, warn, Level};
 use rdkafka::config::ClientConfig;
-use rdkafka::producer::{BaseProducer, BaseRecord};
-use serde::{Deserialize, Serialize};
+use rdkafka::producer::BaseProducer;
+use rdkafka::statistics::Statistics;
+use serde::Serialize;
 use agave_geyser_plugin_interface::geyser_plugin_interface::{
     GeyserPlugin,
     GeyserPluginError,
+    ReplicaBlockInfoVersions,
     ReplicaEntryInfoVersions,
     Result as GeyserResult,
     ReplicaTransactionInfoVersions,
-    ReplicaTransactionInfo,
+    SlotStatus,
 };
-use rdkafka::producer::Producer;
 use agave_logger::setup_with_default;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+use solana_transaction_status::Reward;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::time::Duration;
-use solana_sdk::signature::Signature;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+mod backoff;
+mod catchup;
+mod checkpoint;
+mod compression;
+mod config;
+mod dedup;
+mod digest;
+mod hexdump;
+mod key;
+mod log_throttle;
+mod programs;
+mod sink;
+mod token;
+mod topics;
+mod transaction;
+mod transform;
+mod warmup;
+
+use checkpoint::{Checkpointer, TopicOffsets};
+use config::{KafkaClusterConfig, MissingIndexPolicy, MultiSinkMode, PluginConfig, SerializationErrorPolicy};
+use digest::SlotDigests;
+use key::{KeyContext, KeyStrategy, SlotKey};
+use log_throttle::LogThrottle;
+use sink::{EventSink, KafkaContext, KafkaSink, PendingStats, ThreadedSink};
+use transaction::TxView;
+use transform::{Transformed, WasmTransform};
+use catchup::CatchUp;
+use dedup::RecentSignatures;
+use warmup::Warmup;
+
+/// Schema history:
+/// - 1: slot, idx, num_hashes, executed_tx_count
+/// - 2: idx, num_hashes, executed_tx_count omitted when not in `entry_fields`
 #[derive(Serialize)]
 struct EntryEvent {
+    schema_version: u32,
     slot: u64,
-    idx: usize,
-    num_hashes: u64,
-    executed_tx_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    idx: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_hashes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    executed_tx_count: Option<u64>,
+}
+
+/// Optional `EntryEvent` fields selected by `entry_fields`.
+#[derive(Clone, Copy)]
+struct EntryFields {
+    idx: bool,
+    num_hashes: bool,
+    executed_tx_count: bool,
+}
+
+impl Default for EntryFields {
+    fn default() -> Self {
+        Self {
+            idx: true,
+            num_hashes: true,
+            executed_tx_count: true,
+        }
+    }
+}
+
+impl EntryFields {
+    fn parse(names: &[String]) -> Result<Self, String> {
+        if names.is_empty() {
+            return Ok(Self::default());
+        }
+        let mut fields = Self {
+            idx: false,
+            num_hashes: false,
+            executed_tx_count: false,
+        };
+        for name in names {
+            match name.as_str() {
+                "slot" => {}
+                "idx" => fields.idx = true,
+                "num_hashes" => fields.num_hashes = true,
+                "executed_tx_count" => fields.executed_tx_count = true,
+                _ => return Err(format!("unknown entry_fields entry {name:?}")),
+            }
+        }
+        Ok(fields)
+    }
 }
 
+/// Schema history. `schema_version` was first stamped at 3; earlier
+/// payloads carried no version, so no record is stamped 1 or 2:
+/// - 1 (unstamped): slot, signature, is_vote
+/// - 2 (unstamped): index, optional program_sequence
+/// - 3: optional logs, logs_truncated; first stamped version
+/// - 4: optional recent_blockhash
+/// - 5: optional compute_unit_price
+/// - 6: optional lamport_deltas
+/// - 7: optional signature_count
+/// - 8: optional error_detail
+/// - 9: optional account_count
+/// - 10: optional instruction_count
+/// - 11: optional memo
+/// - 12: optional touched_raydium
 #[derive(Serialize)]
 struct TxEvent {
+    schema_version: u32,
     slot: u64,
     signature: String,
     is_vote: bool,
+    /// Position within the block; null for V0_0_1 notifications.
+    index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    program_sequence: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    logs: Option<Vec<String>>,
+    /// True when at least one log message was cut to `max_log_chars`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    logs_truncated: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recent_blockhash: Option<String>,
+    /// Micro-lamports per compute unit; present (possibly null) only with
+    /// `include_priority_fee`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compute_unit_price: Option<Option<u64>>,
+    /// Write-locked accounts in account-key order, capped at
+    /// `max_lamport_deltas`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lamport_deltas: Option<Vec<LamportDelta>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature_count: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instruction_count: Option<usize>,
+    /// Present (null without a memo) only with `include_memo`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<Option<String>>,
+    /// Present (null on success) only with `include_error_detail`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_detail: Option<Option<TransactionError>>,
+    /// Whether any watched `raydium_programs` program was invoked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    touched_raydium: Option<bool>,
 }
 
-struct RaywatchGeyserPlugin {
-    producer: Option<BaseProducer>,
-    topic: String,
+#[derive(Serialize)]
+struct LamportDelta {
+    account: String,
+    pre: u64,
+    post: u64,
+    delta: i64,
 }
 
-#[derive(Deserialize)]
-struct PluginConfig {
-    #[serde(default = "default_kafka_brokers")]
-    kafka_brokers: String,
+/// Schema history:
+/// - 1: slot, signature, source, destination, mint, amount, authority
+/// - 2: transfer_id
+#[derive(Serialize)]
+struct TransferEvent {
+    schema_version: u32,
+    slot: u64,
+    signature: String,
+    /// `{signature}:{instruction_index}`, where the index counts invoked
+    /// instructions in execution order (each top-level instruction, then
+    /// its inner instructions). Unique per transfer, for deduplication.
+    transfer_id: String,
+    source: String,
+    destination: String,
+    /// Null when the instruction is a plain `transfer` and the source has no
+    /// token balance entry in the status meta.
+    mint: Option<String>,
+    amount: u64,
+    authority: String,
+}
+
+/// One reward from a block's metadata, sent to `reward_topic`.
+///
+/// Schema history:
+/// - 1: slot, pubkey, lamports, reward_type, commission
+#[derive(Serialize)]
+struct RewardEvent<'a> {
+    schema_version: u32,
+    slot: u64,
+    pubkey: &'a str,
+    lamports: i64,
+    /// "fee", "rent", "staking" or "voting"; null when the validator did not
+    /// record one.
+    reward_type: Option<String>,
+    /// Vote account commission, for voting and staking rewards.
+    commission: Option<u8>,
+}
+
+/// Closes a slot's `TxEvent`s with a digest consumers can recompute to
+/// detect loss; see the `digest` module for the construction. Sent to the
+/// main topic once the slot is rooted, by which time every transaction
+/// notification for it has arrived.
+///
+/// Schema history:
+/// - 1: slot, tx_count, slot_digest
+/// - 2: slot_digest XORs per-signature hashes instead of chaining them, so
+///   it no longer depends on event order
+#[derive(Serialize)]
+struct SlotDigestEvent {
+    schema_version: u32,
+    slot: u64,
+    /// `TxEvent`s folded into the digest.
+    tx_count: u64,
+    slot_digest: String,
+}
+
+/// Summary of a librdkafka statistics report, sent to `stats_topic`.
+///
+/// Schema history:
+/// - 1: slot, time, messages_produced, bytes_produced, queue_depth,
+///   queue_bytes, tx_errors, reconnects
+#[derive(Serialize)]
+struct StatsEvent {
+    schema_version: u32,
+    /// Slot being produced when the report was picked up.
+    slot: u64,
+    /// Report time, in seconds since the Unix epoch.
+    time: i64,
+    /// Totals since the producer was created.
+    messages_produced: i64,
+    bytes_produced: i64,
+    /// Messages / bytes waiting in the local producer queue.
+    queue_depth: u64,
+    queue_bytes: u64,
+    /// Summed over brokers, since the producer was created.
+    tx_errors: u64,
+    reconnects: i64,
+}
+
+impl StatsEvent {
+    fn new(slot: u64, stats: &Statistics) -> Self {
+        let brokers = stats.brokers.values();
+        Self {
+            schema_version: Self::SCHEMA_VERSION,
+            slot,
+            time: stats.time,
+            messages_produced: stats.txmsgs,
+            bytes_produced: stats.txmsg_bytes,
+            queue_depth: stats.msg_cnt,
+            queue_bytes: stats.msg_size,
+            tx_errors: brokers.clone().map(|broker| broker.txerrs).sum(),
+            // Every connection after a broker's first.
+            reconnects: brokers
+                .map(|broker| broker.connects.unwrap_or(0).saturating_sub(1).max(0))
+                .sum(),
+        }
+    }
+}
+
+/// Diagnostic record sent to `dead_letter_topic` for events that could not
+/// be produced. A reprocessor can group on `error_kind`, re-derive the event
+/// from `slot` / `signature`, or replay `raw_payload_base64` when present.
+///
+/// Both kinds are encoded the same way: never transformed, but compressed
+/// per `payload_compression` (with `content-encoding`) and carrying the
+/// schema version and `include_monotonic_ts` headers like any other event.
+///
+/// Schema history:
+/// - 1: original_topic, event_kind, error_reason, slot
+/// - 2: error_kind, raw_payload_base64, signature, failed_at_ms
+#[derive(Serialize)]
+struct DeadLetterEvent<'a> {
+    schema_version: u32,
+    original_topic: &'a str,
+    event_kind: &'a str,
+    error_reason: &'a str,
+    error_kind: DeadLetterKind,
+    /// The encoded event after any transform but before compression,
+    /// standard base64; null for `serialization` failures, where there is
+    /// none.
+    raw_payload_base64: Option<String>,
+    slot: u64,
+    /// Source transaction; null for entry and reward events and for `send`
+    /// failures (it is inside the raw payload).
+    signature: Option<&'a str>,
+    failed_at_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum DeadLetterKind {
+    /// The event could not be encoded.
+    Serialization,
+    /// A sink rejected the encoded event, after any retries.
+    Send,
+}
+
+/// What `emit` needs to know about an event besides its payload.
+trait Event: Serialize {
+    /// Short label used in logs and dead letters.
+    const KIND: &'static str;
+    const SCHEMA_VERSION: u32;
+
+    /// Signature of the transaction the event came from, if any.
+    fn signature(&self) -> Option<&str> {
+        None
+    }
 }
 
-fn default_kafka_brokers() -> String {
-    "localhost:9092".to_string()
+impl Event for EntryEvent {
+    const KIND: &'static str = "entry";
+    const SCHEMA_VERSION: u32 = 2;
+}
+
+impl Event for RewardEvent<'_> {
+    const KIND: &'static str = "reward";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+impl Event for TransferEvent {
+    const KIND: &'static str = "transfer";
+    const SCHEMA_VERSION: u32 = 2;
+
+    fn signature(&self) -> Option<&str> {
+        Some(&self.signature)
+    }
+}
+
+impl Event for TxEvent {
+    const KIND: &'static str = "tx";
+    const SCHEMA_VERSION: u32 = 12;
+
+    fn signature(&self) -> Option<&str> {
+        Some(&self.signature)
+    }
+}
+
+impl Event for SlotDigestEvent {
+    const KIND: &'static str = "slot digest";
+    const SCHEMA_VERSION: u32 = 2;
+}
+
+impl Event for StatsEvent {
+    const KIND: &'static str = "stats";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+impl DeadLetterEvent<'_> {
+    const SCHEMA_VERSION: u32 = 2;
+}
+
+/// Any event plus its per-topic `offset`, flattened into one object. Only
+/// used with `emit_topic_offsets`, so it does not bump the individual event
+/// schemas.
+#[derive(Serialize)]
+struct WithOffset<'a, T> {
+    #[serde(flatten)]
+    event: &'a T,
+    offset: u64,
+}
+
+/// Mainnet epoch length, used for `emit_epochs`.
+const SLOTS_PER_EPOCH: u64 = 432_000;
+
+/// Epochs beyond this (~50 years of mainnet) are assumed to be typos.
+const MAX_PLAUSIBLE_EPOCH: u64 = 10_000;
+
+/// Kafka header / Pulsar property carrying the event's `schema_version`.
+const SCHEMA_VERSION_HEADER: &str = "schema_version";
+
+/// Header naming the `payload_compression` codec of compressed payloads.
+const CONTENT_ENCODING_HEADER: &str = "content-encoding";
+
+/// Headers added by `include_monotonic_ts`.
+const INGEST_TIME_MS_HEADER: &str = "ingest_time_ms";
+const INGEST_MONO_NS_HEADER: &str = "ingest_mono_ns";
+const MONO_ANCHOR_HEADER: &str = "mono_anchor_unix_ns";
+
+/// Wall-clock reference for monotonic timestamps, taken together at load.
+struct ClockAnchor {
+    instant: Instant,
+    unix_ns: u64,
+}
+
+impl ClockAnchor {
+    fn now() -> Self {
+        Self {
+            instant: Instant::now(),
+            unix_ns: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn elapsed_ns(&self) -> u64 {
+        self.instant.elapsed().as_nanos() as u64
+    }
+}
+
+fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+struct RaywatchGeyserPlugin {
+    sinks: Vec<Box<dyn EventSink>>,
+    topic: String,
+    config: PluginConfig,
+    warmup: Warmup,
+    catch_up: CatchUp,
+    missing_index_warned: AtomicBool,
+    /// Transactions skipped by `min_fee_lamports`.
+    below_min_fee: AtomicU64,
+    /// When `hexdump_unknown_instructions` last dumped, in Unix ms.
+    last_hexdump_ms: AtomicU64,
+    checkpoint: Option<Checkpointer>,
+    topic_offsets: Arc<TopicOffsets>,
+    recent_signatures: Arc<RecentSignatures>,
+    transform: Option<WasmTransform>,
+    error_log: LogThrottle,
+    raydium_programs: HashSet<Pubkey>,
+    pending_stats: PendingStats,
+    slot_digests: SlotDigests,
+    /// Record key for tx, transfer and reward events.
+    key_strategy: Box<dyn KeyStrategy>,
+    /// Record key for entry events, per `entry_key_format`.
+    entry_key_strategy: Box<dyn KeyStrategy>,
+    entry_fields: EntryFields,
+    clock_anchor: ClockAnchor,
+}
+
+impl Default for RaywatchGeyserPlugin {
+    fn default() -> Self {
+        Self {
+            sinks: Vec::new(),
+            topic: "raydium-swaps-raw".to_string(),
+            config: PluginConfig::default(),
+            warmup: Warmup::new(0),
+            catch_up: CatchUp::new(false, 0, Duration::ZERO),
+            missing_index_warned: AtomicBool::new(false),
+            below_min_fee: AtomicU64::new(0),
+            last_hexdump_ms: AtomicU64::new(0),
+            checkpoint: None,
+            topic_offsets: Arc::default(),
+            recent_signatures: Arc::new(RecentSignatures::new(0)),
+            transform: None,
+            error_log: LogThrottle::new(Duration::ZERO),
+            raydium_programs: HashSet::new(),
+            pending_stats: PendingStats::default(),
+            slot_digests: SlotDigests::default(),
+            key_strategy: Box::new(SlotKey),
+            entry_key_strategy: Box::new(SlotKey),
+            entry_fields: EntryFields::default(),
+            clock_anchor: ClockAnchor::now(),
+        }
+    }
 }
 
 impl fmt::Debug for RaywatchGeyserPlugin {
@@ -55,77 +483,686 @@ impl fmt::Debug for RaywatchGeyserPlugin {
 }
 
 impl RaywatchGeyserPlugin {
-    fn init_kafka(&mut self, brokers: &str) -> GeyserResult<()> {
-        let producer: BaseProducer = ClientConfig::new()
-            .set("bootstrap.servers", brokers)
-            .set("message.timeout.ms", "5000")
-            .create()
+    /// The primary cluster (`kafka_brokers`) followed by `kafka_clusters`.
+    fn kafka_clusters(&self) -> Vec<KafkaClusterConfig> {
+        let primary = KafkaClusterConfig {
+            name: "Kafka".to_string(),
+            brokers: self.config.kafka_brokers.clone(),
+            properties: HashMap::new(),
+            topics: HashMap::new(),
+        };
+        std::iter::once(primary).chain(self.config.kafka_clusters.iter().cloned()).collect()
+    }
+
+    /// Connection settings shared by the producer and admin client of `cluster`.
+    fn kafka_client_config(&self, cluster: &KafkaClusterConfig) -> GeyserResult<ClientConfig> {
+        let mut client_config = ClientConfig::new();
+        client_config.set("bootstrap.servers", &cluster.brokers);
+        if let Some(enable) = self.config.socket_keepalive_enable {
+            client_config.set("socket.keepalive.enable", enable.to_string());
+        }
+        let timeouts = [
+            ("socket.timeout.ms", self.config.socket_timeout_ms, 10..=300_000),
+            (
+                "socket.connection.setup.timeout.ms",
+                self.config.socket_connection_setup_timeout_ms,
+                1_000..=i32::MAX as u32,
+            ),
+        ];
+        for (key, value, range) in timeouts {
+            let Some(value) = value else { continue };
+            if !range.contains(&value) {
+                return Err(GeyserPluginError::ConfigFileReadError {
+                    msg: format!(
+                        "{key} must be between {} and {}, got {value}",
+                        range.start(),
+                        range.end()
+                    ),
+                });
+            }
+            client_config.set(key, value.to_string());
+        }
+        for (key, value) in self.config.backoff.librdkafka_properties() {
+            client_config.set(key, value);
+        }
+        for (key, value) in &cluster.properties {
+            client_config.set(key, value);
+        }
+        Ok(client_config)
+    }
+
+    /// Statistics are only collected from the primary cluster.
+    fn init_kafka(&mut self, cluster: KafkaClusterConfig, mut client_config: ClientConfig, primary: bool) -> GeyserResult<()> {
+        if !cluster.properties.contains_key("message.timeout.ms") {
+            client_config.set("message.timeout.ms", "5000");
+        }
+        let stats = (primary && self.config.emit_stats_events).then(|| {
+            client_config.set("statistics.interval.ms", self.config.stats_interval_ms.max(1).to_string());
+            Arc::clone(&self.pending_stats)
+        });
+
+        let producer: BaseProducer<KafkaContext> = client_config
+            .create_with_context(KafkaContext::new(stats))
             .map_err(|e| GeyserPluginError::Custom(Box::new(e)))?;
 
-        self.producer = Some(producer);
+        let name = if primary { cluster.name } else { format!("Kafka[{}]", cluster.name) };
+        info!("RaywatchGeyserPlugin: connected to {name} at {}", cluster.brokers);
+        self.sinks.push(Box::new(KafkaSink::new(
+            name,
+            producer,
+            cluster.topics,
+            self.config.queue_full_retries,
+        )));
         Ok(())
     }
 
-    fn send_tx_event(&self, slot: u64, signature: &Signature, is_vote: bool) {
-        if let Some(producer) = &self.producer {
-            let event = TxEvent {
-                slot,
-                signature: signature.to_string(),
-                is_vote,
-            };
+    #[cfg(feature = "pulsar")]
+    fn init_pulsar(&mut self) -> GeyserResult<()> {
+        if let Some(pulsar) = &self.config.pulsar {
+            let sink = self
+                .config
+                .backoff
+                .retry("connecting to Pulsar", || sink::PulsarSink::new(pulsar, &self.topic))
+                .map_err(|e| GeyserPluginError::Custom(e.into()))?;
+            info!(
+                "RaywatchGeyserPlugin: producing to Pulsar topic {} at {}",
+                pulsar.topic, pulsar.service_url
+            );
+            self.sinks.push(Box::new(sink));
+        }
+        Ok(())
+    }
 
-            match serde_json::to_vec(&event) {
-                Ok(payload) => {
-                    let key = slot.to_be_bytes();
+    #[cfg(not(feature = "pulsar"))]
+    fn init_pulsar(&mut self) -> GeyserResult<()> {
+        if self.config.pulsar.is_some() {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: "`pulsar` is configured but the plugin was built without the `pulsar` feature"
+                    .to_string(),
+            });
+        }
+        Ok(())
+    }
 
-                    let record = BaseRecord::to(&self.topic)
-                        .key(&key)
-                        .payload(&payload);
+    #[cfg(feature = "kinesis")]
+    fn init_kinesis(&mut self) -> GeyserResult<()> {
+        if let Some(kinesis) = &self.config.kinesis {
+            let backoff = self.config.backoff.clone();
+            let sink = backoff
+                .retry("connecting to Kinesis", || sink::KinesisSink::new(kinesis, &self.topic, backoff.clone()))
+                .map_err(|e| GeyserPluginError::Custom(e.into()))?;
+            info!("RaywatchGeyserPlugin: putting records to Kinesis stream {}", kinesis.stream_name);
+            self.sinks.push(Box::new(sink));
+        }
+        Ok(())
+    }
 
-                    if let Err((e, _owned_msg)) = producer.send(record) {
-                        error!("RaywatchGeyserPlugin: failed to send tx to Kafka: {e}");
-                    }
+    #[cfg(not(feature = "kinesis"))]
+    fn init_kinesis(&mut self) -> GeyserResult<()> {
+        if self.config.kinesis.is_some() {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: "`kinesis` is configured but the plugin was built without the `kinesis` feature"
+                    .to_string(),
+            });
+        }
+        Ok(())
+    }
 
-                    // ok for now in dev; later we can optimize
-                    let _ = producer.flush(Duration::from_millis(0));
-                }
-                Err(e) => {
-                    error!("RaywatchGeyserPlugin: failed to serialize tx: {e}");
+    /// Every topic the current config can produce to, labeled by the events
+    /// it carries. Labels may share a topic.
+    fn topic_roles(&self) -> Vec<(&'static str, &str)> {
+        let mut roles = vec![("tx/entry/transfer", self.topic.as_str())];
+        if self.config.separate_backfill
+            && let Some(backfill_topic) = &self.config.backfill_topic
+        {
+            roles.push(("backfill", backfill_topic));
+        }
+        if self.config.on_serialization_error == SerializationErrorPolicy::Dlq || self.config.dead_letter_failed_sends {
+            roles.push(("dead letter", &self.config.dead_letter_topic));
+        }
+        if self.config.emit_rewards {
+            roles.push(("reward", &self.config.reward_topic));
+        }
+        if self.config.emit_stats_events {
+            roles.push(("stats", &self.config.stats_topic));
+        }
+        if self.config.complex_tx_threshold.is_some() {
+            roles.push(("complex tx", &self.config.complex_tx_topic));
+        }
+        roles
+    }
+
+    /// Every distinct topic the current config can produce to.
+    fn produced_topics(&self) -> Vec<&str> {
+        let mut topics: Vec<&str> = Vec::new();
+        for (_, topic) in self.topic_roles() {
+            if !topics.contains(&topic) {
+                topics.push(topic);
+            }
+        }
+        topics
+    }
+
+    /// Pairs of differently shaped events that some sink delivers to the
+    /// same destination, after its topic renames or stream mapping:
+    /// `(sink, role, other role, destination)`.
+    fn topic_collisions(&self) -> Vec<(&str, &'static str, &'static str, &str)> {
+        let roles = self.topic_roles();
+        let mut collisions = Vec::new();
+        for sink in &self.sinks {
+            for (i, (role, topic)) in roles.iter().enumerate() {
+                let destination = sink.destination(topic);
+                for (other_role, _) in roles[i + 1..]
+                    .iter()
+                    .filter(|(_, other)| sink.destination(other) == destination)
+                {
+                    collisions.push((sink.name(), *role, *other_role, destination));
                 }
             }
         }
+        collisions
     }
 
-    fn send_entry_event(&self, slot: u64, index: usize, num_hashes: u64, executed_transaction_count: u64) {
-        if let Some(producer) = &self.producer {
-            let event = EntryEvent {
-                slot,
-                idx: index,
-                num_hashes,
-                executed_tx_count: executed_transaction_count,
-            };
+    /// Warns when differently shaped events end up on one topic or stream,
+    /// which leaves consumers with a mixed-schema stream.
+    fn warn_on_topic_collisions(&self) {
+        if self.config.allow_shared_topics {
+            return;
+        }
+        for (sink, role, other_role, destination) in self.topic_collisions() {
+            warn!(
+                "RaywatchGeyserPlugin: {role} and {other_role} events are both produced to {destination} on {sink}; set allow_shared_topics if this is intended"
+            );
+        }
+    }
+
+    /// Hands one serialized event to every sink, then polls each so delivery
+    /// callbacks are served without blocking the notification thread.
+    /// Returns whether at least one sink accepted the event.
+    fn produce(&self, topic: &str, kind: &str, slot: u64, key: &[u8], payload: &[u8], headers: &[(&str, &[u8])]) -> bool {
+        let transformed;
+        let payload = match &self.transform {
+            Some(transform) => match transform.apply(payload) {
+                Ok(Transformed::Send(bytes)) => {
+                    transformed = bytes;
+                    transformed.as_slice()
+                }
+                Ok(Transformed::Drop) => return false,
+                Err(e) => {
+                    error!("RaywatchGeyserPlugin: transform failed for {kind} in slot {slot}: {e}; sending untransformed");
+                    payload
+                }
+            },
+            None => payload,
+        };
 
-            match serde_json::to_vec(&event) {
-                Ok(payload) => {
-                    let key = slot.to_be_bytes();
+        self.deliver(topic, kind, slot, key, payload, headers)
+    }
 
-                    let record = BaseRecord::to(&self.topic)
-                        .key(&key)
-                        .payload(&payload);
+    /// The part of `produce` after the transform: compresses the payload and
+    /// sends it to every sink. Dead letters enter here, untransformed.
+    fn deliver(&self, topic: &str, kind: &str, slot: u64, key: &[u8], payload: &[u8], headers: &[(&str, &[u8])]) -> bool {
+        self.with_compression(kind, slot, payload, headers, |body, headers| {
+            self.send_to_sinks(topic, kind, slot, key, payload, body, headers)
+        })
+    }
 
-                    if let Err((e, _owned_msg)) = producer.send(record) {
-                        error!("RaywatchGeyserPlugin: failed to send to Kafka: {e}");
+    /// Calls `f` with `payload` compressed per `payload_compression` and
+    /// `headers` plus the matching `content-encoding`, or with both as they
+    /// are when compression is off or fails.
+    fn with_compression<R>(
+        &self,
+        kind: &str,
+        slot: u64,
+        payload: &[u8],
+        headers: &[(&str, &[u8])],
+        f: impl FnOnce(&[u8], &[(&str, &[u8])]) -> R,
+    ) -> R {
+        let Some(encoding) = self.config.payload_compression.content_encoding() else {
+            return f(payload, headers);
+        };
+        match self.config.payload_compression.compress(payload) {
+            Ok(compressed) => {
+                let headers: Vec<(&str, &[u8])> = headers
+                    .iter()
+                    .copied()
+                    .chain([(CONTENT_ENCODING_HEADER, encoding.as_bytes())])
+                    .collect();
+                f(&compressed, &headers)
+            }
+            Err(e) => {
+                error!("RaywatchGeyserPlugin: {encoding} compression failed for {kind} in slot {slot}: {e}; sending uncompressed");
+                f(payload, headers)
+            }
+        }
+    }
+
+    /// Sends the encoded `body` to every sink, dead-lettering the
+    /// uncompressed `payload` for sinks that reject it.
+    #[allow(clippy::too_many_arguments)]
+    fn send_to_sinks(
+        &self,
+        topic: &str,
+        kind: &str,
+        slot: u64,
+        key: &[u8],
+        payload: &[u8],
+        body: &[u8],
+        headers: &[(&str, &[u8])],
+    ) -> bool {
+        let mut handed_off = false;
+        for sink in &self.sinks {
+            // Never retried here, so a failing sink cannot stall the
+            // notification thread; `retry_failed_sends` retries on the sink's
+            // own thread instead.
+            match sink.send(topic, key, body, headers) {
+                Ok(()) => handed_off = true,
+                Err(e) => {
+                    self.error_log
+                        .error(format!("RaywatchGeyserPlugin: failed to send {kind} to {}: {e}", sink.name()));
+                    if self.config.dead_letter_failed_sends && topic != self.config.dead_letter_topic {
+                        self.send_failed_dead_letter(sink.as_ref(), topic, kind, slot, key, payload, &e);
                     }
+                }
+            }
+            sink.poll();
+        }
+
+        // Dropped or undeliverable events do not advance the checkpoint.
+        if handed_off && let Some(checkpoint) = &self.checkpoint {
+            checkpoint.record(slot);
+        }
+        handed_off
+    }
 
-                    if let Err(e) = producer.flush(Duration::from_millis(0)) {
-                        error!("RaywatchGeyserPlugin: flush error: {e}");
+    /// Calls `f` with the headers every record carries: `schema_version`,
+    /// plus the `include_monotonic_ts` timestamps.
+    fn with_headers<R>(&self, schema_version: u32, f: impl FnOnce(&[(&str, &[u8])]) -> R) -> R {
+        let version = schema_version.to_string();
+        if !self.config.include_monotonic_ts {
+            return f(&[(SCHEMA_VERSION_HEADER, version.as_bytes())]);
+        }
+        let mono_ns = self.clock_anchor.elapsed_ns().to_string();
+        let time_ms = unix_time_ms().to_string();
+        let anchor = self.clock_anchor.unix_ns.to_string();
+        f(&[
+            (SCHEMA_VERSION_HEADER, version.as_bytes()),
+            (INGEST_TIME_MS_HEADER, time_ms.as_bytes()),
+            (INGEST_MONO_NS_HEADER, mono_ns.as_bytes()),
+            (MONO_ANCHOR_HEADER, anchor.as_bytes()),
+        ])
+    }
+
+    /// Serializes `event` and produces it to `topic`, applying
+    /// `on_serialization_error` if encoding fails. Returns whether at least
+    /// one sink accepted the event.
+    fn emit<T: Event>(&self, topic: &str, slot: u64, key: &[u8], event: &T) -> bool {
+        let sent = self.encode_and_send(topic, event, |payload| {
+            self.with_headers(T::SCHEMA_VERSION, |headers| {
+                self.produce(topic, T::KIND, slot, key, payload, headers)
+            })
+        });
+        let handed_off = match sent {
+            Ok(handed_off) => handed_off,
+            Err(e) => {
+                match self.config.on_serialization_error {
+                    SerializationErrorPolicy::Drop => {}
+                    SerializationErrorPolicy::PanicSafeSkip => {
+                        error!("RaywatchGeyserPlugin: failed to serialize {}: {e}", T::KIND);
                     }
+                    SerializationErrorPolicy::Dlq => self.send_dead_letter(DeadLetterEvent {
+                        schema_version: DeadLetterEvent::SCHEMA_VERSION,
+                        original_topic: topic,
+                        event_kind: T::KIND,
+                        error_reason: &e.to_string(),
+                        error_kind: DeadLetterKind::Serialization,
+                        raw_payload_base64: None,
+                        slot,
+                        signature: event.signature(),
+                        failed_at_ms: unix_time_ms(),
+                    }),
                 }
+                false
+            }
+        };
+        self.emit_pending_stats(slot);
+        handed_off
+    }
+
+    /// Emits a `StatsEvent` if the sink polls delivered a statistics report;
+    /// the lock is only taken when stats events are on.
+    fn emit_pending_stats(&self, slot: u64) {
+        if !self.config.emit_stats_events {
+            return;
+        }
+        let stats = self.pending_stats.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(stats) = stats {
+            let key = self.key_strategy.key(&KeyContext { slot, index: None });
+            self.emit(&self.config.stats_topic, slot, &key, &StatsEvent::new(slot, &stats));
+        }
+    }
+
+    /// Serializes `record` for `topic` and passes it to `send`, which reports
+    /// whether any sink took it. With `emit_topic_offsets` the record carries
+    /// the topic's next offset; an undelivered event gives its offset back
+    /// when no later one was taken meanwhile, and otherwise leaves a gap.
+    fn encode_and_send<T: Serialize>(
+        &self,
+        topic: &str,
+        record: &T,
+        send: impl FnOnce(&[u8]) -> bool,
+    ) -> serde_json::Result<bool> {
+        if !self.config.emit_topic_offsets {
+            return serde_json::to_vec(record).map(|payload| send(&payload));
+        }
+        let offset = self.topic_offsets.take(topic);
+        let sent = serde_json::to_vec(&WithOffset { event: record, offset }).map(|payload| send(&payload));
+        if !matches!(sent, Ok(true)) && !self.topic_offsets.give_back(topic, offset) {
+            self.error_log.warn(format!(
+                "RaywatchGeyserPlugin: undelivered events left gaps in the offsets of topic {topic}"
+            ));
+        }
+        sent
+    }
+
+    fn send_dead_letter(&self, record: DeadLetterEvent<'_>) {
+        let topic = &self.config.dead_letter_topic;
+        let slot = record.slot;
+        let key = self.key_strategy.key(&KeyContext { slot, index: None });
+        let sent = self.encode_and_send(topic, &record, |payload| {
+            self.with_headers(DeadLetterEvent::SCHEMA_VERSION, |headers| {
+                self.deliver(topic, "dead letter", slot, &key, payload, headers)
+            })
+        });
+        if let Err(e) = sent {
+            error!(
+                "RaywatchGeyserPlugin: failed to serialize dead letter for {}: {e}",
+                record.event_kind
+            );
+        }
+    }
+
+    /// Dead-letters an event `failed` rejected to `dead_letter_sink`, or back
+    /// to `failed` when none is configured. Failures here are only logged.
+    #[allow(clippy::too_many_arguments)]
+    fn send_failed_dead_letter(
+        &self,
+        failed: &dyn EventSink,
+        original_topic: &str,
+        kind: &str,
+        slot: u64,
+        key: &[u8],
+        payload: &[u8],
+        error: &anyhow::Error,
+    ) {
+        let target = match &self.config.dead_letter_sink {
+            Some(name) => match self.sinks.iter().find(|sink| sink.name() == name) {
+                Some(sink) => sink.as_ref(),
+                None => return,
+            },
+            None => failed,
+        };
+        let record = DeadLetterEvent {
+            schema_version: DeadLetterEvent::SCHEMA_VERSION,
+            original_topic,
+            event_kind: kind,
+            error_reason: &format!("{}: {error}", failed.name()),
+            error_kind: DeadLetterKind::Send,
+            raw_payload_base64: Some(BASE64_STANDARD.encode(payload)),
+            slot,
+            signature: None,
+            failed_at_ms: unix_time_ms(),
+        };
+        let topic = &self.config.dead_letter_topic;
+        let result = self.encode_and_send(topic, &record, |dead_letter| {
+            let sent = self.with_headers(DeadLetterEvent::SCHEMA_VERSION, |headers| {
+                self.with_compression("dead letter", slot, dead_letter, headers, |body, headers| {
+                    target.send(topic, key, body, headers)
+                })
+            });
+            match sent {
+                Ok(()) => true,
                 Err(e) => {
-                    error!("RaywatchGeyserPlugin: failed to serialize event: {e}");
+                    self.error_log.error(format!(
+                        "RaywatchGeyserPlugin: failed to dead-letter {kind} to {}: {e}",
+                        target.name()
+                    ));
+                    false
                 }
             }
+        });
+        if let Err(e) = result {
+            self.error_log.error(format!(
+                "RaywatchGeyserPlugin: failed to serialize dead letter for {kind}: {e}"
+            ));
+        }
+    }
+
+    /// Blocking final flush of every sink, bounded by `shutdown_flush_timeout_ms`.
+    fn flush_on_shutdown(&self) {
+        let timeout = Duration::from_millis(self.config.shutdown_flush_timeout_ms);
+        for sink in &self.sinks {
+            match sink.flush(timeout) {
+                Ok(()) => match sink.delivery_counts() {
+                    Some((delivered, failed)) => info!(
+                        "RaywatchGeyserPlugin: flushed {} on shutdown ({delivered} delivered, {failed} failed)",
+                        sink.name()
+                    ),
+                    None => info!("RaywatchGeyserPlugin: flushed {} on shutdown", sink.name()),
+                },
+                Err(e) => self.error_log.error(match sink.in_flight() {
+                    Some(undelivered) => format!(
+                        "RaywatchGeyserPlugin: {} flush did not complete within {timeout:?}: {e}; {undelivered} messages undelivered",
+                        sink.name()
+                    ),
+                    None => format!(
+                        "RaywatchGeyserPlugin: {} flush did not complete within {timeout:?}: {e}",
+                        sink.name()
+                    ),
+                }),
+            }
+        }
+    }
+
+    /// Whether events for `slot` may be emitted: past warmup and inside
+    /// `emit_epochs`.
+    fn allow_slot(&self, slot: u64) -> bool {
+        self.warmup.allow(slot)
+            && (self.config.emit_epochs.is_empty() || self.config.emit_epochs.contains(&(slot / SLOTS_PER_EPOCH)))
+    }
+
+    /// Topic for tx/entry/transfer/digest events: the main topic, or during
+    /// catch-up the backfill topic, or `None` when backfill is dropped.
+    fn live_topic(&self) -> Option<&str> {
+        if self.catch_up.is_caught_up() {
+            return Some(&self.topic);
+        }
+        self.catch_up.count_backfilled();
+        if self.config.backfill_topic.is_none() {
+            self.error_log.warn(
+                "RaywatchGeyserPlugin: dropping backfill events while catching up; set backfill_topic to keep them"
+                    .to_string(),
+            );
         }
+        self.config.backfill_topic.as_deref()
+    }
+
+    fn send_tx_event(&self, slot: u64, tx: &TxView<'_>) {
+        if !self.allow_slot(slot) {
+            return;
+        }
+        if !self.sinks.is_empty() {
+            let program_sequence = self
+                .config
+                .include_program_sequence
+                .then(|| tx.program_sequence(self.config.max_program_sequence_len));
+            let (logs, logs_truncated) = if self.config.include_logs {
+                let (logs, truncated) = tx.logs(self.config.max_log_chars);
+                (Some(logs), Some(truncated))
+            } else {
+                (None, None)
+            };
+
+            let mut event = TxEvent {
+                schema_version: TxEvent::SCHEMA_VERSION,
+                slot,
+                signature: tx.signature.to_string(),
+                is_vote: tx.is_vote,
+                index: tx.index,
+                program_sequence,
+                logs,
+                logs_truncated,
+                recent_blockhash: self
+                    .config
+                    .include_recent_blockhash
+                    .then(|| tx.recent_blockhash.to_string()),
+                compute_unit_price: self.config.include_priority_fee.then(|| tx.compute_unit_price()),
+                lamport_deltas: self.config.include_lamport_deltas.then(|| {
+                    tx.lamport_balances(self.config.max_lamport_deltas)
+                        .into_iter()
+                        .map(|(account, pre, post)| LamportDelta {
+                            account: account.to_string(),
+                            pre,
+                            post,
+                            delta: post as i64 - pre as i64,
+                        })
+                        .collect()
+                }),
+                signature_count: self.config.include_signature_count.then(|| tx.signature_count()),
+                account_count: self.config.include_account_count.then(|| tx.account_keys.len()),
+                instruction_count: self
+                    .config
+                    .include_instruction_count
+                    .then(|| tx.invoked_instructions().count()),
+                memo: self.config.include_memo.then(|| tx.memo(self.config.max_memo_chars)),
+                error_detail: self.config.include_error_detail.then(|| tx.meta.status.clone().err()),
+                touched_raydium: self
+                    .config
+                    .include_touched_raydium
+                    .then(|| tx.invokes_any(&self.raydium_programs)),
+            };
+
+            let key = self.key_strategy.key(&KeyContext { slot, index: None });
+            let Some(topic) = self.live_topic() else {
+                return;
+            };
+            // Dropped and undeliverable events are left out of both, so a
+            // replay re-sends them and the digest covers exactly what
+            // reached the topic.
+            let handed_off = self.emit(topic, slot, &key, &event);
+            if handed_off && self.recent_signatures.is_enabled() {
+                self.recent_signatures.insert(slot, tx.signature);
+            }
+            if handed_off && self.config.emit_slot_digest {
+                self.slot_digests.record(slot, tx.signature);
+            }
+
+            // The complex stream is real-time only, so it skips catch-up.
+            if let Some(threshold) = self.config.complex_tx_threshold
+                && self.catch_up.is_caught_up()
+            {
+                let instruction_count = tx.invoked_instructions().count();
+                if instruction_count > threshold {
+                    event.program_sequence = Some(tx.program_sequence(usize::MAX));
+                    event.instruction_count = Some(instruction_count);
+                    self.emit(&self.config.complex_tx_topic, slot, &key, &event);
+                }
+            }
+        }
+    }
+
+    fn send_transfer_events(&self, slot: u64, tx: &TxView<'_>) {
+        if !self.allow_slot(slot) || self.sinks.is_empty() || !tx.invokes_any(&self.raydium_programs) {
+            return;
+        }
+
+        for (instruction_index, ix) in tx.invoked_instructions().enumerate() {
+            let Some(transfer) = token::decode_transfer(tx, ix) else {
+                continue;
+            };
+
+            let signature = tx.signature.to_string();
+            let event = TransferEvent {
+                schema_version: TransferEvent::SCHEMA_VERSION,
+                slot,
+                transfer_id: format!("{signature}:{instruction_index}"),
+                signature,
+                source: transfer.source,
+                destination: transfer.destination,
+                mint: transfer.mint,
+                amount: transfer.amount,
+                authority: transfer.authority,
+            };
+
+            let key = self.key_strategy.key(&KeyContext { slot, index: None });
+            let Some(topic) = self.live_topic() else {
+                return;
+            };
+            self.emit(topic, slot, &key, &event);
+        }
+    }
+
+    fn send_entry_event(&self, slot: u64, index: usize, num_hashes: u64, executed_transaction_count: u64) {
+        if !self.allow_slot(slot) || !self.config.emit_entry_events {
+            return;
+        }
+        if !self.sinks.is_empty() {
+            let event = EntryEvent {
+                schema_version: EntryEvent::SCHEMA_VERSION,
+                slot,
+                idx: self.entry_fields.idx.then_some(index),
+                num_hashes: self.entry_fields.num_hashes.then_some(num_hashes),
+                executed_tx_count: self
+                    .entry_fields
+                    .executed_tx_count
+                    .then_some(executed_transaction_count),
+            };
+
+            let key = self.entry_key_strategy.key(&KeyContext {
+                slot,
+                index: Some(index),
+            });
+            let Some(topic) = self.live_topic() else {
+                return;
+            };
+            self.emit(topic, slot, &key, &event);
+        }
+    }
+
+    fn send_reward_events(&self, slot: u64, rewards: &[Reward]) {
+        if !self.allow_slot(slot) || self.sinks.is_empty() {
+            return;
+        }
+        for reward in rewards {
+            let event = RewardEvent {
+                schema_version: RewardEvent::SCHEMA_VERSION,
+                slot,
+                pubkey: &reward.pubkey,
+                lamports: reward.lamports,
+                reward_type: reward.reward_type.map(|reward_type| reward_type.to_string()),
+                commission: reward.commission,
+            };
+            let key = self.key_strategy.key(&KeyContext { slot, index: None });
+            self.emit(&self.config.reward_topic, slot, &key, &event);
+        }
+    }
+
+    fn send_slot_digest(&self, slot: u64) {
+        let digest = self.slot_digests.finish(slot);
+        if !self.allow_slot(slot) || self.sinks.is_empty() {
+            return;
+        }
+        let event = SlotDigestEvent {
+            schema_version: SlotDigestEvent::SCHEMA_VERSION,
+            slot,
+            tx_count: digest.count,
+            slot_digest: digest.digest.to_string(),
+        };
+        let key = self.key_strategy.key(&KeyContext { slot, index: None });
+        let Some(topic) = self.live_topic() else {
+            return;
+        };
+        self.emit(topic, slot, &key, &event);
     }
 
     fn handle_tx_versions(
@@ -133,35 +1170,84 @@ impl RaywatchGeyserPlugin {
         tx: ReplicaTransactionInfoVersions<'_>,
         slot: u64,
     ) -> GeyserResult<()> {
-        match tx {
-            ReplicaTransactionInfoVersions::V0_0_1(tx_info) => {
-                info!(
-                    "RaywatchGeyserPlugin: got tx in slot {slot} (is_vote={})",
-                    tx_info.is_vote
-                );
-                self.send_tx_event(slot, tx_info.signature, tx_info.is_vote);
-            }
-            ReplicaTransactionInfoVersions::V0_0_2(tx_info) => {
-                info!(
-                    "RaywatchGeyserPlugin: got tx in slot {slot} (is_vote={}, index={})",
-                    tx_info.is_vote, tx_info.index
-                );
-                self.send_tx_event(slot, tx_info.signature, tx_info.is_vote);
-            }
-            ReplicaTransactionInfoVersions::V0_0_3(tx_info) => {
-                info!(
-                    "RaywatchGeyserPlugin: got tx in slot {slot} (is_vote={}, index={})",
-                    tx_info.is_vote, tx_info.index
-                );
-                self.send_tx_event(slot, tx_info.signature, tx_info.is_vote);
+        // Votes dominate mainnet traffic: drop them before anything else.
+        if self.config.skip_votes && transaction::is_vote(&tx) {
+            return Ok(());
+        }
+
+        let view = TxView::new(&tx);
+        if view.meta.fee < self.config.min_fee_lamports {
+            self.below_min_fee.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+        if view.index.is_none() {
+            match self.config.v0_0_1_policy {
+                MissingIndexPolicy::Emit => {}
+                MissingIndexPolicy::Skip => return Ok(()),
+                MissingIndexPolicy::Warn => {
+                    if !self.missing_index_warned.swap(true, Ordering::Relaxed) {
+                        warn!(
+                            "RaywatchGeyserPlugin: validator sends V0_0_1 transaction notifications; events will have no index (first seen in slot {slot})"
+                        );
+                    }
+                }
             }
-            _ => {
-                info!(
-                    "RaywatchGeyserPlugin: notify_transaction called with unsupported transaction info version at slot {slot}"
+        }
+        match view.index {
+            Some(index) => trace!(
+                "RaywatchGeyserPlugin: got tx in slot {slot} (is_vote={}, index={index})",
+                view.is_vote
+            ),
+            None => trace!(
+                "RaywatchGeyserPlugin: got tx in slot {slot} (is_vote={})",
+                view.is_vote
+            ),
+        }
+        if self.recent_signatures.is_enabled() && self.recent_signatures.is_duplicate(view.signature) {
+            return Ok(());
+        }
+        self.send_tx_event(slot, &view);
+        if self.config.emit_token_transfers {
+            self.send_transfer_events(slot, &view);
+        }
+        if self.config.hexdump_unknown_instructions {
+            self.hexdump_instructions(slot, &view);
+        }
+        Ok(())
+    }
+
+    /// Logs a hex dump of each watched-program instruction in `tx`, at most
+    /// once per `hexdump_interval_ms`.
+    fn hexdump_instructions(&self, slot: u64, tx: &TxView<'_>) {
+        if !log_enabled!(Level::Debug) {
+            return;
+        }
+        let now = unix_time_ms();
+        let last = self.last_hexdump_ms.load(Ordering::Relaxed);
+        if now.saturating_sub(last) < self.config.hexdump_interval_ms || !tx.invokes_any(&self.raydium_programs) {
+            return;
+        }
+        if self
+            .last_hexdump_ms
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+
+        for (position, ix) in tx.invoked_instructions().enumerate() {
+            let Some(program_id) = tx.account_keys.get(usize::from(ix.program_id_index)) else {
+                continue;
+            };
+            if self.raydium_programs.contains(program_id) {
+                debug!(
+                    "RaywatchGeyserPlugin: invoked instruction {position} of {} (slot {slot}) to {program_id}, {} bytes:\n{}",
+                    tx.signature,
+                    ix.data.len(),
+                    hexdump::hexdump(&ix.data)
                 );
             }
-        };
-        Ok(())
+        }
     }
 
     fn handle_entry_versions(
@@ -189,11 +1275,6 @@ impl RaywatchGeyserPlugin {
                 );
                 self.send_entry_event(info.slot, info.index, info.num_hashes, info.executed_transaction_count);
             }
-            other => {
-                info!(
-                    "RaywatchGeyserPlugin: notify_entry called with unsupported entry info version"
-                );
-            }
         };
         Ok(())
     }
@@ -208,32 +1289,154 @@ impl GeyserPlugin for RaywatchGeyserPlugin {
         setup_with_default("info");
         info!("RaywatchGeyserPlugin: loading with config {config_file}");
 
-        let brokers = match std::fs::read_to_string(config_file) {
+        self.config = match std::fs::read_to_string(config_file) {
             Ok(contents) => match serde_json::from_str::<PluginConfig>(&contents) {
-                Ok(cfg) => cfg.kafka_brokers,
+                Ok(cfg) => cfg,
                 Err(e) => {
                     error!(
-                        "RaywatchGeyserPlugin: failed to parse config {config_file}: {e}; using default localhost:9092"
+                        "RaywatchGeyserPlugin: failed to parse config {config_file}: {e}; using defaults"
                     );
-                    default_kafka_brokers()
+                    PluginConfig::default()
                 }
             },
             Err(e) => {
                 error!(
-                    "RaywatchGeyserPlugin: failed to read config {config_file}: {e}; using default localhost:9092"
+                    "RaywatchGeyserPlugin: failed to read config {config_file}: {e}; using defaults"
                 );
-                default_kafka_brokers()
+                PluginConfig::default()
             }
         };
 
-        self.init_kafka(&brokers)?;
-        info!("RaywatchGeyserPlugin: connected to Kafka at {brokers}");
+        self.raydium_programs = programs::parse_program_ids(&self.config.raydium_programs)
+            .map_err(|msg| GeyserPluginError::ConfigFileReadError { msg })?;
+        for program_id in programs::unrecognized(&self.raydium_programs) {
+            warn!(
+                "RaywatchGeyserPlugin: raydium_programs entry {program_id} is not a known Raydium/DEX program; check for typos"
+            );
+        }
+
+        if let Some(epoch) = self.config.emit_epochs.iter().find(|epoch| **epoch > MAX_PLAUSIBLE_EPOCH) {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: format!("emit_epochs entry {epoch} is not a plausible epoch"),
+            });
+        }
+
+        if self.config.emit_topic_offsets && self.config.checkpoint_path.is_none() {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: "emit_topic_offsets needs checkpoint_path, or offsets restart at 0 on every load".to_string(),
+            });
+        }
+
+        if self.config.retry_failed_sends && self.config.multi_sink_mode != MultiSinkMode::Parallel {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: "retry_failed_sends needs multi_sink_mode = \"parallel\", so retries run on the sink threads"
+                    .to_string(),
+            });
+        }
+        if self.config.retry_failed_sends && self.config.queue_full_retries > 0 {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: "retry_failed_sends and queue_full_retries both retry Kafka sends; set only one".to_string(),
+            });
+        }
+
+        self.entry_key_strategy = key::for_entries(self.config.entry_key_format);
+        self.entry_fields = EntryFields::parse(&self.config.entry_fields)
+            .map_err(|msg| GeyserPluginError::ConfigFileReadError { msg })?;
+        self.error_log = LogThrottle::new(Duration::from_secs(self.config.log_cooldown_secs));
+        self.clock_anchor = ClockAnchor::now();
+        self.warmup = Warmup::new(self.config.warmup_slots);
+        self.catch_up = CatchUp::new(
+            self.config.separate_backfill,
+            self.config.backfill_max_lag_secs,
+            Duration::from_secs(self.config.backfill_max_secs),
+        );
+        if self.config.warmup_slots > 0 {
+            info!(
+                "RaywatchGeyserPlugin: suppressing events for the first {} slots after load",
+                self.config.warmup_slots
+            );
+        }
+
+        self.recent_signatures = Arc::new(RecentSignatures::new(self.config.dedup_window_slots));
+        self.checkpoint = self.config.checkpoint_path.as_deref().map(|path| {
+            Checkpointer::start(
+                path,
+                Duration::from_secs(self.config.checkpoint_interval_secs.max(1)),
+                Arc::clone(&self.topic_offsets),
+                Arc::clone(&self.recent_signatures),
+            )
+        });
+
+        if let Some(path) = &self.config.transform_wasm {
+            let transform = WasmTransform::load(path, self.config.transform_fuel)
+                .map_err(|e| GeyserPluginError::Custom(e.into()))?;
+            info!("RaywatchGeyserPlugin: loaded transform module {path}");
+            self.transform = Some(transform);
+        }
+
+        for (i, cluster) in self.kafka_clusters().into_iter().enumerate() {
+            let client_config = self.kafka_client_config(&cluster)?;
+            if self.config.auto_create_topics {
+                let produced = self.produced_topics();
+                let topics: Vec<(&str, &str)> = produced
+                    .iter()
+                    .map(|topic| (*topic, cluster.topics.get(*topic).map_or(*topic, String::as_str)))
+                    .collect();
+                self.config.backoff.retry_if(
+                    "creating topics",
+                    || topics::create_missing_topics(&self.config, &client_config, &topics),
+                    topics::is_transient,
+                )?;
+            }
+            self.init_kafka(cluster, client_config, i == 0)?;
+        }
+        self.init_pulsar()?;
+        self.init_kinesis()?;
+        if self.config.multi_sink_mode == MultiSinkMode::Parallel {
+            let cooldown = Duration::from_secs(self.config.log_cooldown_secs);
+            let retry = self.config.retry_failed_sends.then(|| self.config.backoff.clone());
+            self.sinks = std::mem::take(&mut self.sinks)
+                .into_iter()
+                .map(|sink| {
+                    ThreadedSink::spawn(sink, self.config.sink_queue_capacity, cooldown, retry.clone())
+                        .map(|sink| Box::new(sink) as Box<dyn EventSink>)
+                        .map_err(|e| GeyserPluginError::Custom(e.into()))
+                })
+                .collect::<GeyserResult<_>>()?;
+        }
+
+        if let Some(name) = &self.config.dead_letter_sink
+            && !self.sinks.iter().any(|sink| sink.name() == name)
+        {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: format!("dead_letter_sink {name:?} does not match any configured sink"),
+            });
+        }
+        self.warn_on_topic_collisions();
         Ok(())
     }
 
     fn on_unload(&mut self) {
         info!("RaywatchGeyserPlugin: unloading");
-        self.producer = None;
+        if self.config.min_fee_lamports > 0 {
+            info!(
+                "RaywatchGeyserPlugin: skipped {} transactions below min_fee_lamports={}",
+                self.below_min_fee.load(Ordering::Relaxed),
+                self.config.min_fee_lamports
+            );
+        }
+        if self.recent_signatures.is_enabled() {
+            info!(
+                "RaywatchGeyserPlugin: skipped {} duplicate transactions within dedup_window_slots={}",
+                self.recent_signatures.hits(),
+                self.config.dedup_window_slots
+            );
+        }
+        self.flush_on_shutdown();
+        if let Some(mut checkpoint) = self.checkpoint.take() {
+            checkpoint.finish();
+        }
+        self.sinks.clear();
     }
 
     fn notify_transaction(
@@ -245,15 +1448,44 @@ impl GeyserPlugin for RaywatchGeyserPlugin {
     }
 
     fn notify_entry(&self, entry: ReplicaEntryInfoVersions<'_>) -> GeyserResult<()> {
+        if !self.config.enable_entry_notifications {
+            return Ok(());
+        }
         self.handle_entry_versions(entry)
     }
 
+    fn notify_block_metadata(&self, blockinfo: ReplicaBlockInfoVersions<'_>) -> GeyserResult<()> {
+        let (slot, rewards, block_time) = match blockinfo {
+            ReplicaBlockInfoVersions::V0_0_1(info) => (info.slot, info.rewards, info.block_time),
+            ReplicaBlockInfoVersions::V0_0_2(info) => (info.slot, info.rewards, info.block_time),
+            ReplicaBlockInfoVersions::V0_0_3(info) => (info.slot, info.rewards, info.block_time),
+            ReplicaBlockInfoVersions::V0_0_4(info) => (info.slot, info.rewards.rewards.as_slice(), info.block_time),
+        };
+        if self.config.emit_rewards {
+            self.send_reward_events(slot, rewards);
+        }
+        self.catch_up.observe_block(slot, block_time);
+        Ok(())
+    }
+
+    fn update_slot_status(&self, slot: u64, _parent: Option<u64>, status: &SlotStatus) -> GeyserResult<()> {
+        if !self.config.emit_slot_digest {
+            return Ok(());
+        }
+        match status {
+            SlotStatus::Rooted => self.send_slot_digest(slot),
+            SlotStatus::Dead(_) => self.slot_digests.discard(slot),
+            _ => {}
+        }
+        Ok(())
+    }
+
     fn transaction_notifications_enabled(&self) -> bool {
         true
     }
 
     fn entry_notifications_enabled(&self) -> bool {
-        true
+        self.config.enable_entry_notifications
     }
 
     fn account_data_notifications_enabled(&self) -> bool {
@@ -262,10 +1494,201 @@ impl GeyserPlugin for RaywatchGeyserPlugin {
 }
 
 #[unsafe(no_mangle)]
+#[allow(improper_ctypes_definitions)]
 pub extern "C" fn _create_plugin() -> *mut dyn GeyserPlugin {
-    let plugin = RaywatchGeyserPlugin {
-        producer: None,
-        topic: "raydium-swaps-raw".to_string(),
-    };
+    let plugin = RaywatchGeyserPlugin::default();
     Box::into_raw(Box::new(plugin))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agave_geyser_plugin_interface::geyser_plugin_interface::ReplicaEntryInfoV2;
+    use std::sync::Mutex;
+
+    struct Record {
+        topic: String,
+        key: Vec<u8>,
+        payload: Vec<u8>,
+    }
+
+    /// Keeps every event it is sent; clones share the records.
+    #[derive(Clone, Default)]
+    struct RecordingSink {
+        records: Arc<Mutex<Vec<Record>>>,
+        /// Topic renames reported by `destination`.
+        renames: HashMap<String, String>,
+    }
+
+    impl RecordingSink {
+        fn records(&self) -> std::sync::MutexGuard<'_, Vec<Record>> {
+            self.records.lock().unwrap()
+        }
+    }
+
+    impl EventSink for RecordingSink {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn send(&self, topic: &str, key: &[u8], payload: &[u8], _headers: &[(&str, &[u8])]) -> anyhow::Result<()> {
+            self.records().push(Record {
+                topic: topic.to_string(),
+                key: key.to_vec(),
+                payload: payload.to_vec(),
+            });
+            Ok(())
+        }
+
+        fn poll(&self) {}
+
+        fn flush(&self, _timeout: Duration) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn in_flight(&self) -> Option<usize> {
+            None
+        }
+
+        fn destination<'a>(&'a self, topic: &'a str) -> &'a str {
+            self.renames.get(topic).map_or(topic, String::as_str)
+        }
+    }
+
+    fn plugin(config: PluginConfig, sink: &RecordingSink) -> RaywatchGeyserPlugin {
+        RaywatchGeyserPlugin {
+            sinks: vec![Box::new(sink.clone())],
+            config,
+            ..RaywatchGeyserPlugin::default()
+        }
+    }
+
+    fn entry(slot: u64) -> ReplicaEntryInfoV2<'static> {
+        ReplicaEntryInfoV2 {
+            slot,
+            index: 3,
+            num_hashes: 12,
+            hash: &[0; 32],
+            executed_transaction_count: 2,
+            starting_transaction_index: 0,
+        }
+    }
+
+    #[test]
+    fn entry_notifications_disabled_sends_nothing() {
+        let sink = RecordingSink::default();
+        let plugin = plugin(
+            PluginConfig {
+                enable_entry_notifications: false,
+                ..PluginConfig::default()
+            },
+            &sink,
+        );
+
+        plugin.notify_entry(ReplicaEntryInfoVersions::V0_0_2(&entry(7))).unwrap();
+        assert!(!plugin.entry_notifications_enabled());
+        assert!(sink.records().is_empty());
+    }
+
+    #[test]
+    fn entry_notifications_enabled_sends_entry_event() {
+        let sink = RecordingSink::default();
+        let plugin = plugin(PluginConfig::default(), &sink);
+
+        plugin.notify_entry(ReplicaEntryInfoVersions::V0_0_2(&entry(7))).unwrap();
+        let records = sink.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].topic, plugin.topic);
+        assert_eq!(records[0].key, 7u64.to_be_bytes());
+        let event: serde_json::Value = serde_json::from_slice(&records[0].payload).unwrap();
+        assert_eq!(event["slot"], 7);
+    }
+
+    /// An event whose encoding always fails.
+    struct Unserializable;
+
+    impl Serialize for Unserializable {
+        fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("unserializable"))
+        }
+    }
+
+    impl Event for Unserializable {
+        const KIND: &'static str = "unserializable";
+        const SCHEMA_VERSION: u32 = 1;
+
+        fn signature(&self) -> Option<&str> {
+            Some("sig")
+        }
+    }
+
+    fn emit_unserializable(policy: SerializationErrorPolicy) -> RecordingSink {
+        let sink = RecordingSink::default();
+        let plugin = plugin(
+            PluginConfig {
+                on_serialization_error: policy,
+                ..PluginConfig::default()
+            },
+            &sink,
+        );
+        plugin.emit(&plugin.topic, 7, &7u64.to_be_bytes(), &Unserializable);
+        sink
+    }
+
+    #[test]
+    fn serialization_error_drop_sends_nothing() {
+        assert!(emit_unserializable(SerializationErrorPolicy::Drop).records().is_empty());
+    }
+
+    #[test]
+    fn serialization_error_panic_safe_skip_sends_nothing() {
+        assert!(emit_unserializable(SerializationErrorPolicy::PanicSafeSkip).records().is_empty());
+    }
+
+    #[test]
+    fn serialization_error_dlq_sends_dead_letter() {
+        let sink = emit_unserializable(SerializationErrorPolicy::Dlq);
+        let records = sink.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].topic, PluginConfig::default().dead_letter_topic);
+        assert_eq!(records[0].key, 7u64.to_be_bytes());
+        let dead_letter: serde_json::Value = serde_json::from_slice(&records[0].payload).unwrap();
+        assert_eq!(dead_letter["error_kind"], "serialization");
+        assert_eq!(dead_letter["event_kind"], "unserializable");
+        assert_eq!(dead_letter["original_topic"], "raydium-swaps-raw");
+        assert_eq!(dead_letter["signature"], "sig");
+        assert_eq!(dead_letter["slot"], 7);
+        assert!(dead_letter["raw_payload_base64"].is_null());
+    }
+
+    #[test]
+    fn distinct_topics_do_not_collide() {
+        let plugin = plugin(
+            PluginConfig {
+                emit_rewards: true,
+                ..PluginConfig::default()
+            },
+            &RecordingSink::default(),
+        );
+        assert!(plugin.topic_collisions().is_empty());
+    }
+
+    #[test]
+    fn sink_rename_onto_another_topic_collides() {
+        let sink = RecordingSink {
+            renames: HashMap::from([("raydium-rewards".to_string(), "raydium-swaps-raw".to_string())]),
+            ..RecordingSink::default()
+        };
+        let plugin = plugin(
+            PluginConfig {
+                emit_rewards: true,
+                ..PluginConfig::default()
+            },
+            &sink,
+        );
+        assert_eq!(
+            plugin.topic_collisions(),
+            [("recording", "tx/entry/transfer", "reward", "raydium-swaps-raw")]
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use log::{log, Level};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Distinct messages tracked before the table is reset.
+const MAX_TRACKED: usize = 1024;
+
+struct Seen {
+    logged_at: Instant,
+    suppressed: u64,
+}
+
+/// Collapses identical error lines: the first occurrence in a window is
+/// logged, repeats are counted, and the next line after the window closes
+/// carries the count, e.g. "failed to send tx to Kafka: ... (x1523 in last 5s)".
+pub(crate) struct LogThrottle {
+    window: Duration,
+    seen: Mutex<HashMap<String, Seen>>,
+}
+
+impl LogThrottle {
+    /// A zero `window` disables throttling.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn error(&self, message: String) {
+        self.log(Level::Error, message);
+    }
+
+    pub fn warn(&self, message: String) {
+        self.log(Level::Warn, message);
+    }
+
+    fn log(&self, level: Level, message: String) {
+        if self.window.is_zero() {
+            log!(level, "{message}");
+            return;
+        }
+
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        if seen.len() >= MAX_TRACKED && !seen.contains_key(&message) {
+            seen.clear();
+        }
+
+        match seen.get_mut(&message) {
+            Some(entry) if now.duration_since(entry.logged_at) < self.window => {
+                entry.suppressed += 1;
+            }
+            Some(entry) => {
+                if entry.suppressed > 0 {
+                    log!(
+                        level,
+                        "{message} (x{} in last {}s)",
+                        entry.suppressed + 1,
+                        now.duration_since(entry.logged_at).as_secs()
+                    );
+                } else {
+                    log!(level, "{message}");
+                }
+                entry.logged_at = now;
+                entry.suppressed = 0;
+            }
+            None => {
+                log!(level, "{message}");
+                seen.insert(
+                    message,
+                    Seen {
+                        logged_at: now,
+                        suppressed: 0,
+                    },
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Raydium programs watched when `raydium_programs` is left empty.
+pub(crate) const RAYDIUM_PROGRAMS: &[(&str, &str)] = &[
+    ("amm_v4", "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8"),
+    ("clmm", "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK"),
+    ("cpmm", "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C"),
+    ("stable", "5quBtoiQqxF9Jv6KYKctB59NT3gtJD2Y65kdnB1Uev3h"),
+    ("amm_routing", "routeUGWgWzqBWFcrCfv8tritsqukccJPu3q5GPP3xS"),
+];
+
+/// Other well-known DEX programs that are valid, if unusual, to watch.
+pub(crate) const OTHER_DEX_PROGRAMS: &[(&str, &str)] = &[
+    ("jupiter_v6", "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"),
+    ("orca_whirlpool", "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc"),
+    ("orca_v2", "9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP"),
+    ("meteora_dlmm", "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo"),
+    ("openbook", "srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX"),
+    ("pump_fun", "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P"),
+];
+
+pub(crate) const SPL_TOKEN: Pubkey = Pubkey::from_str_const("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub(crate) const SPL_TOKEN_2022: Pubkey = Pubkey::from_str_const("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub(crate) const SPL_MEMO: Pubkey = Pubkey::from_str_const("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+pub(crate) const SPL_MEMO_V1: Pubkey = Pubkey::from_str_const("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo");
+pub(crate) const COMPUTE_BUDGET: Pubkey = Pubkey::from_str_const("ComputeBudget111111111111111111111111111111");
+
+/// Parses the configured program ids, falling back to the built-in Raydium
+/// set when none are given.
+pub(crate) fn parse_program_ids(configured: &[String]) -> Result<HashSet<Pubkey>, String> {
+    if configured.is_empty() {
+        return Ok(RAYDIUM_PROGRAMS
+            .iter()
+            .map(|(_, id)| Pubkey::from_str_const(id))
+            .collect());
+    }
+
+    configured
+        .iter()
+        .map(|id| Pubkey::from_str(id).map_err(|e| format!("invalid program id {id:?} in raydium_programs: {e}")))
+        .collect()
+}
+
+/// Configured ids that are neither Raydium nor another known DEX program;
+/// most often a typo that would silently match nothing.
+pub(crate) fn unrecognized(programs: &HashSet<Pubkey>) -> impl Iterator<Item = &Pubkey> {
+    programs.iter().filter(|program_id| {
+        !RAYDIUM_PROGRAMS
+            .iter()
+            .chain(OTHER_DEX_PROGRAMS)
+            .any(|(_, known)| Pubkey::from_str_const(known) == **program_id)
+    })
+}
@@ -0,0 +1,763 @@
+use rdkafka::client::ClientContext;
+use rdkafka::error::KafkaError;
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{BaseProducer, BaseRecord, DeliveryResult, Producer, ProducerContext};
+use rdkafka::statistics::Statistics;
+use rdkafka::types::RDKafkaErrorCode;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub(crate) use self::threaded::ThreadedSink;
+#[cfg(feature = "kinesis")]
+pub(crate) use self::kinesis::KinesisSink;
+#[cfg(feature = "pulsar")]
+pub(crate) use self::pulsar::PulsarSink;
+
+/// A destination for serialized events.
+///
+/// Sends happen on the validator's notification threads, so implementations
+/// should enqueue and return rather than wait for acknowledgement.
+pub(crate) trait EventSink: Send + Sync {
+    /// Short label used in logs.
+    fn name(&self) -> &str;
+
+    /// Enqueue `payload` for `topic`, partitioned by `key`, with message
+    /// `headers` (Kafka headers / Pulsar properties).
+    fn send(&self, topic: &str, key: &[u8], payload: &[u8], headers: &[(&str, &[u8])]) -> anyhow::Result<()>;
+
+    /// Serve delivery callbacks without blocking; called after each send.
+    fn poll(&self);
+
+    /// Push out anything buffered, waiting at most `timeout`.
+    fn flush(&self, timeout: Duration) -> anyhow::Result<()>;
+
+    /// Messages handed to the sink but not yet acknowledged, when known.
+    fn in_flight(&self) -> Option<usize>;
+
+    /// Where this sink actually delivers events for `topic` (renamed topic,
+    /// stream), for spotting topics that end up sharing a destination.
+    fn destination<'a>(&'a self, topic: &'a str) -> &'a str {
+        topic
+    }
+
+    /// `(delivered, failed)` delivery reports so far, when tracked.
+    fn delivery_counts(&self) -> Option<(u64, u64)> {
+        None
+    }
+}
+
+/// Latest librdkafka statistics not yet picked up by the plugin.
+pub(crate) type PendingStats = Arc<Mutex<Option<Statistics>>>;
+
+/// Producer context counting delivery reports and, when `stats` is set,
+/// keeping the most recent statistics report (only delivered when
+/// `statistics.interval.ms` is set) instead of logging it.
+#[derive(Default)]
+pub(crate) struct KafkaContext {
+    pub stats: Option<PendingStats>,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl KafkaContext {
+    pub fn new(stats: Option<PendingStats>) -> Self {
+        Self {
+            stats,
+            ..Self::default()
+        }
+    }
+}
+
+impl ClientContext for KafkaContext {
+    fn stats(&self, statistics: Statistics) {
+        if let Some(stats) = &self.stats {
+            *stats.lock().unwrap_or_else(|e| e.into_inner()) = Some(statistics);
+        }
+    }
+}
+
+impl ProducerContext for KafkaContext {
+    type DeliveryOpaque = ();
+
+    fn delivery(&self, delivery_result: &DeliveryResult<'_>, _delivery_opaque: Self::DeliveryOpaque) {
+        let counter = if delivery_result.is_ok() { &self.delivered } else { &self.failed };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) struct KafkaSink {
+    name: String,
+    producer: BaseProducer<KafkaContext>,
+    /// Plugin topic → topic on this cluster, for renamed topics.
+    topics: HashMap<String, String>,
+    /// Extra attempts while librdkafka's local queue is full, each after a
+    /// non-blocking poll for delivery reports that may free up room.
+    queue_full_retries: u32,
+}
+
+impl KafkaSink {
+    pub fn new(
+        name: String,
+        producer: BaseProducer<KafkaContext>,
+        topics: HashMap<String, String>,
+        queue_full_retries: u32,
+    ) -> Self {
+        Self {
+            name,
+            producer,
+            topics,
+            queue_full_retries,
+        }
+    }
+}
+
+impl EventSink for KafkaSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn destination<'a>(&'a self, topic: &'a str) -> &'a str {
+        self.topics.get(topic).map_or(topic, String::as_str)
+    }
+
+    fn send(&self, topic: &str, key: &[u8], payload: &[u8], headers: &[(&str, &[u8])]) -> anyhow::Result<()> {
+        let topic = self.destination(topic);
+        let mut record = BaseRecord::to(topic).key(key).payload(payload);
+        if !headers.is_empty() {
+            let owned = headers.iter().fold(OwnedHeaders::new(), |acc, (key, value)| {
+                acc.insert(Header { key, value: Some(*value) })
+            });
+            record = record.headers(owned);
+        }
+
+        let mut attempt = 0;
+        loop {
+            match self.producer.send(record) {
+                Ok(()) => return Ok(()),
+                Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), rejected))
+                    if attempt < self.queue_full_retries =>
+                {
+                    // Never wait here: this runs on the notification thread.
+                    self.producer.poll(Duration::ZERO);
+                    record = rejected;
+                    attempt += 1;
+                }
+                Err((e, _owned_msg)) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn poll(&self) {
+        self.producer.poll(Duration::ZERO);
+    }
+
+    fn flush(&self, timeout: Duration) -> anyhow::Result<()> {
+        self.producer.flush(timeout)?;
+        Ok(())
+    }
+
+    fn in_flight(&self) -> Option<usize> {
+        usize::try_from(self.producer.in_flight_count()).ok()
+    }
+
+    fn delivery_counts(&self) -> Option<(u64, u64)> {
+        let context = self.producer.context();
+        Some((
+            context.delivered.load(Ordering::Relaxed),
+            context.failed.load(Ordering::Relaxed),
+        ))
+    }
+}
+
+mod threaded {
+    use super::EventSink;
+    use crate::backoff::Backoff;
+    use crate::log_throttle::LogThrottle;
+    use log::warn;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::mpsc::{self, SyncSender, TrySendError};
+    use std::sync::Arc;
+    use std::thread::JoinHandle;
+    use std::time::{Duration, Instant};
+
+    /// How long dropping the sink waits for its worker to stop. Shutdown
+    /// has already spent the flush timeout by then, so this stays short.
+    const STOP_TIMEOUT: Duration = Duration::from_secs(1);
+
+    struct Outgoing {
+        topic: String,
+        key: Vec<u8>,
+        payload: Vec<u8>,
+        headers: Vec<(String, Vec<u8>)>,
+    }
+
+    /// Runs another sink on its own thread behind a bounded queue, so a slow
+    /// sink does not hold up the notification thread or the other sinks.
+    /// `send` only reports enqueue failures (queue full); the inner sink's
+    /// own send errors are retried per `retry`, if set, and logged by the
+    /// worker.
+    pub(crate) struct ThreadedSink {
+        inner: Arc<dyn EventSink>,
+        queue: Option<SyncSender<Outgoing>>,
+        queued: Arc<AtomicUsize>,
+        /// Tells the worker to stop sending and abandon its queue.
+        stop: Arc<AtomicBool>,
+        worker: Option<JoinHandle<()>>,
+    }
+
+    impl ThreadedSink {
+        pub fn spawn(
+            inner: Box<dyn EventSink>,
+            capacity: usize,
+            error_cooldown: Duration,
+            retry: Option<Backoff>,
+        ) -> anyhow::Result<Self> {
+            let inner: Arc<dyn EventSink> = Arc::from(inner);
+            let (queue, rx) = mpsc::sync_channel::<Outgoing>(capacity.max(1));
+            let queued = Arc::new(AtomicUsize::new(0));
+            let stop = Arc::new(AtomicBool::new(false));
+            let worker = {
+                let inner = Arc::clone(&inner);
+                let queued = Arc::clone(&queued);
+                let stop = Arc::clone(&stop);
+                std::thread::Builder::new()
+                    .name(format!("raywatchSink{}", inner.name()))
+                    .spawn(move || {
+                        let error_log = LogThrottle::new(error_cooldown);
+                        for message in rx {
+                            if stop.load(Ordering::Relaxed) {
+                                break;
+                            }
+                            let headers: Vec<(&str, &[u8])> = message
+                                .headers
+                                .iter()
+                                .map(|(key, value)| (key.as_str(), value.as_slice()))
+                                .collect();
+                            let send = || inner.send(&message.topic, &message.key, &message.payload, &headers);
+                            let sent = match &retry {
+                                Some(backoff) => backoff.retry_with(send, |_, e, _| {
+                                    if stop.load(Ordering::Relaxed) {
+                                        return false;
+                                    }
+                                    error_log.warn(format!(
+                                        "RaywatchGeyserPlugin: sending to {} failed: {e}; retrying",
+                                        inner.name()
+                                    ));
+                                    true
+                                }),
+                                None => send(),
+                            };
+                            if let Err(e) = sent {
+                                error_log.error(format!(
+                                    "RaywatchGeyserPlugin: failed to send to {}: {e}",
+                                    inner.name()
+                                ));
+                            }
+                            inner.poll();
+                            queued.fetch_sub(1, Ordering::Relaxed);
+                        }
+                    })?
+            };
+
+            Ok(Self {
+                inner,
+                queue: Some(queue),
+                queued,
+                stop,
+                worker: Some(worker),
+            })
+        }
+    }
+
+    impl EventSink for ThreadedSink {
+        fn name(&self) -> &str {
+            self.inner.name()
+        }
+
+        fn destination<'a>(&'a self, topic: &'a str) -> &'a str {
+            self.inner.destination(topic)
+        }
+
+        fn send(&self, topic: &str, key: &[u8], payload: &[u8], headers: &[(&str, &[u8])]) -> anyhow::Result<()> {
+            let Some(queue) = &self.queue else {
+                anyhow::bail!("sink worker stopped");
+            };
+            let message = Outgoing {
+                topic: topic.to_string(),
+                key: key.to_vec(),
+                payload: payload.to_vec(),
+                headers: headers.iter().map(|(key, value)| (key.to_string(), value.to_vec())).collect(),
+            };
+            self.queued.fetch_add(1, Ordering::Relaxed);
+            queue.try_send(message).map_err(|e| {
+                self.queued.fetch_sub(1, Ordering::Relaxed);
+                match e {
+                    TrySendError::Full(_) => anyhow::anyhow!("sink queue full"),
+                    TrySendError::Disconnected(_) => anyhow::anyhow!("sink worker stopped"),
+                }
+            })
+        }
+
+        fn poll(&self) {
+            // The worker polls the inner sink after each send.
+        }
+
+        fn flush(&self, timeout: Duration) -> anyhow::Result<()> {
+            let deadline = Instant::now() + timeout;
+            while self.queued.load(Ordering::Relaxed) > 0 {
+                if Instant::now() >= deadline {
+                    anyhow::bail!("timed out draining the sink queue");
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            self.inner.flush(deadline.saturating_duration_since(Instant::now()))
+        }
+
+        fn in_flight(&self) -> Option<usize> {
+            let queued = self.queued.load(Ordering::Relaxed);
+            Some(queued + self.inner.in_flight().unwrap_or(0))
+        }
+
+        fn delivery_counts(&self) -> Option<(u64, u64)> {
+            self.inner.delivery_counts()
+        }
+    }
+
+    /// Stops the worker rather than draining its queue, which could take
+    /// forever against a dead broker; runs after the shutdown flush. A worker
+    /// stuck in a send is detached after `STOP_TIMEOUT`.
+    impl Drop for ThreadedSink {
+        fn drop(&mut self) {
+            self.stop.store(true, Ordering::Relaxed);
+            drop(self.queue.take());
+            let Some(worker) = self.worker.take() else {
+                return;
+            };
+            let deadline = Instant::now() + STOP_TIMEOUT;
+            while !worker.is_finished() && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            let abandoned = self.queued.load(Ordering::Relaxed);
+            if worker.is_finished() {
+                let _ = worker.join();
+            } else {
+                warn!(
+                    "RaywatchGeyserPlugin: {} worker did not stop within {STOP_TIMEOUT:?}; detaching it",
+                    self.inner.name()
+                );
+            }
+            if abandoned > 0 {
+                warn!(
+                    "RaywatchGeyserPlugin: abandoned {abandoned} queued messages for {} on shutdown",
+                    self.inner.name()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(feature = "pulsar")]
+mod pulsar {
+    use super::EventSink;
+    use crate::config::PulsarConfig;
+    use ::pulsar::{producer, Pulsar, TokioExecutor};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Produces each topic's events to its Pulsar topic: the main topic to
+    /// `topic`, others as mapped in `topics` or under their own name. The
+    /// event key becomes the partition key (hex) and the ordering key (raw
+    /// bytes).
+    pub(crate) struct PulsarSink {
+        runtime: tokio::runtime::Runtime,
+        client: Pulsar<TokioExecutor>,
+        producer_name: String,
+        topics: HashMap<String, String>,
+        /// Producers by Pulsar topic, created on first use.
+        producers: Mutex<HashMap<String, producer::Producer<TokioExecutor>>>,
+    }
+
+    impl PulsarSink {
+        pub fn new(config: &PulsarConfig, main_topic: &str) -> anyhow::Result<Self> {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("raywatchPulsar")
+                .enable_all()
+                .build()?;
+
+            let mut topics = config.topics.clone();
+            topics.insert(main_topic.to_string(), config.topic.clone());
+            let client = runtime.block_on(Pulsar::builder(config.service_url.as_str(), TokioExecutor).build())?;
+            let sink = Self {
+                runtime,
+                client,
+                producer_name: config.producer_name.clone(),
+                topics,
+                producers: Mutex::new(HashMap::new()),
+            };
+            // Fail at load on an unreachable or unauthorized main topic.
+            let mut producers = sink.producers.lock().unwrap_or_else(|e| e.into_inner());
+            sink.producer(&mut producers, &config.topic)?;
+            drop(producers);
+            Ok(sink)
+        }
+
+        /// `pulsar_topic`'s producer, created if this is its first event.
+        fn producer<'a>(
+            &self,
+            producers: &'a mut HashMap<String, producer::Producer<TokioExecutor>>,
+            pulsar_topic: &str,
+        ) -> anyhow::Result<&'a mut producer::Producer<TokioExecutor>> {
+            if !producers.contains_key(pulsar_topic) {
+                let producer = self.runtime.block_on(
+                    self.client
+                        .producer()
+                        .with_topic(pulsar_topic)
+                        .with_name(self.producer_name.as_str())
+                        .build(),
+                )?;
+                producers.insert(pulsar_topic.to_string(), producer);
+            }
+            Ok(producers.get_mut(pulsar_topic).expect("inserted above"))
+        }
+    }
+
+    impl EventSink for PulsarSink {
+        fn name(&self) -> &str {
+            "Pulsar"
+        }
+
+        fn destination<'a>(&'a self, topic: &'a str) -> &'a str {
+            self.topics.get(topic).map_or(topic, String::as_str)
+        }
+
+        fn send(&self, topic: &str, key: &[u8], payload: &[u8], headers: &[(&str, &[u8])]) -> anyhow::Result<()> {
+            let message = producer::Message {
+                payload: payload.to_vec(),
+                properties: headers
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), String::from_utf8_lossy(value).into_owned()))
+                    .collect(),
+                partition_key: Some(key.iter().map(|b| format!("{b:02x}")).collect()),
+                ordering_key: Some(key.to_vec()),
+                ..Default::default()
+            };
+
+            let pulsar_topic = self.destination(topic);
+            let mut producers = self.producers.lock().unwrap_or_else(|e| e.into_inner());
+            let producer = self.producer(&mut producers, pulsar_topic)?;
+            // Only waits for the message to be queued; the receipt is not awaited.
+            let _receipt = self.runtime.block_on(producer.send_non_blocking(message))?;
+            Ok(())
+        }
+
+        fn poll(&self) {
+            // The client drives its own I/O on `runtime`; nothing to serve here.
+        }
+
+        fn flush(&self, timeout: Duration) -> anyhow::Result<()> {
+            let mut producers = self.producers.lock().unwrap_or_else(|e| e.into_inner());
+            self.runtime.block_on(async {
+                tokio::time::timeout(timeout, async {
+                    for producer in producers.values_mut() {
+                        producer.send_batch().await?;
+                    }
+                    Ok::<_, ::pulsar::Error>(())
+                })
+                .await
+            })??;
+            Ok(())
+        }
+
+        fn in_flight(&self) -> Option<usize> {
+            // Send receipts are not tracked, so the backlog is unknown.
+            None
+        }
+    }
+}
+
+#[cfg(feature = "kinesis")]
+mod kinesis {
+    use super::EventSink;
+    use crate::backoff::Backoff;
+    use crate::config::KinesisConfig;
+    use aws_sdk_kinesis::Client;
+    use aws_sdk_kinesis::primitives::Blob;
+    use aws_sdk_kinesis::types::PutRecordsRequestEntry;
+    use log::error;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    /// Kinesis' per-request record limit for `PutRecords`.
+    const MAX_BATCH: usize = 500;
+
+    struct Batch {
+        records: Vec<PutRecordsRequestEntry>,
+        started_at: Instant,
+    }
+
+    /// Puts events to Kinesis streams, batched per stream into `PutRecords`
+    /// calls that run on a background runtime. The main topic goes to
+    /// `stream_name` and other topics to their `streams` entry; unmapped ones
+    /// share `stream_name` with a `{topic}/` prefix on the partition key. The
+    /// event key (hex) is the partition key. Kinesis records carry no
+    /// metadata, so headers are dropped.
+    pub(crate) struct KinesisSink {
+        runtime: tokio::runtime::Runtime,
+        client: Client,
+        main_topic: String,
+        stream_name: String,
+        streams: HashMap<String, String>,
+        batch_size: usize,
+        linger: Duration,
+        max_in_flight: usize,
+        backoff: Backoff,
+        /// Buffered records by stream name.
+        batches: Mutex<HashMap<String, Batch>>,
+        /// Records handed to `PutRecords` and not yet accepted or given up on.
+        in_flight: Arc<AtomicUsize>,
+    }
+
+    impl KinesisSink {
+        pub fn new(config: &KinesisConfig, main_topic: &str, backoff: Backoff) -> anyhow::Result<Self> {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("raywatchKinesis")
+                .enable_all()
+                .build()?;
+
+            let client = runtime.block_on(async {
+                let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest());
+                if let Some(region) = &config.region {
+                    loader = loader.region(aws_config::Region::new(region.clone()));
+                }
+                if let Some(endpoint_url) = &config.endpoint_url {
+                    loader = loader.endpoint_url(endpoint_url);
+                }
+                let client = Client::new(&loader.load().await);
+                // Fail at load on a missing stream or bad credentials.
+                for stream_name in std::iter::once(&config.stream_name).chain(config.streams.values()) {
+                    client
+                        .describe_stream_summary()
+                        .stream_name(stream_name)
+                        .send()
+                        .await?;
+                }
+                anyhow::Ok(client)
+            })?;
+
+            Ok(Self {
+                runtime,
+                client,
+                main_topic: main_topic.to_string(),
+                stream_name: config.stream_name.clone(),
+                streams: config.streams.clone(),
+                batch_size: config.batch_size.clamp(1, MAX_BATCH),
+                linger: Duration::from_millis(config.linger_ms),
+                max_in_flight: config.max_in_flight.max(1),
+                backoff,
+                batches: Mutex::new(HashMap::new()),
+                in_flight: Arc::new(AtomicUsize::new(0)),
+            })
+        }
+
+        /// The stream `topic` goes to, and the partition key prefix tagging
+        /// its records there.
+        fn route<'a>(&'a self, topic: &'a str) -> (&'a str, Option<&'a str>) {
+            match self.streams.get(topic) {
+                Some(stream_name) => (stream_name, None),
+                None if topic == self.main_topic => (&self.stream_name, None),
+                None => (&self.stream_name, Some(topic)),
+            }
+        }
+
+        /// Hands the buffered records to the runtime.
+        fn put_batch(&self, stream_name: &str, batch: &mut Batch) {
+            if batch.records.is_empty() {
+                return;
+            }
+            let records = std::mem::take(&mut batch.records);
+            self.in_flight.fetch_add(records.len(), Ordering::Relaxed);
+            self.runtime.spawn(put_records(
+                self.client.clone(),
+                stream_name.to_string(),
+                records,
+                self.backoff.clone(),
+                self.in_flight.clone(),
+            ));
+        }
+    }
+
+    /// Puts `records`, re-putting only the ones Kinesis rejected (typically
+    /// `ProvisionedThroughputExceededException`) until `backoff` is used up.
+    async fn put_records(
+        client: Client,
+        stream_name: String,
+        mut records: Vec<PutRecordsRequestEntry>,
+        backoff: Backoff,
+        in_flight: Arc<AtomicUsize>,
+    ) {
+        let total = records.len();
+        let mut attempt = 0;
+        loop {
+            let result = client
+                .put_records()
+                .stream_name(&stream_name)
+                .set_records(Some(records.clone()))
+                .send()
+                .await;
+            let last_error = match result {
+                Ok(output) if output.failed_record_count().unwrap_or(0) == 0 => {
+                    records.clear();
+                    None
+                }
+                Ok(output) => {
+                    // Results line up with the request entries.
+                    let results = output.records();
+                    let mut rejected = results.iter().map(|entry| entry.error_code().is_some());
+                    records.retain(|_| rejected.next().unwrap_or(true));
+                    results.iter().find_map(|entry| entry.error_code()).map(str::to_string)
+                }
+                Err(e) => Some(aws_sdk_kinesis::error::DisplayErrorContext(e).to_string()),
+            };
+
+            match last_error {
+                None => break,
+                Some(_) if attempt < backoff.max_retries => {
+                    tokio::time::sleep(backoff.delay(attempt)).await;
+                    attempt += 1;
+                }
+                Some(e) => {
+                    error!(
+                        "RaywatchGeyserPlugin: dropping {} of {total} Kinesis records after {} retries: {e}",
+                        records.len(),
+                        backoff.max_retries
+                    );
+                    break;
+                }
+            }
+        }
+        in_flight.fetch_sub(total, Ordering::Relaxed);
+    }
+
+    impl EventSink for KinesisSink {
+        fn name(&self) -> &str {
+            "Kinesis"
+        }
+
+        /// The stream; tagged topics sharing `stream_name` still count as
+        /// sharing a destination.
+        fn destination<'a>(&'a self, topic: &'a str) -> &'a str {
+            self.route(topic).0
+        }
+
+        fn send(&self, topic: &str, key: &[u8], payload: &[u8], _headers: &[(&str, &[u8])]) -> anyhow::Result<()> {
+            let (stream_name, tag) = self.route(topic);
+            let mut partition_key = tag.map(|tag| format!("{tag}/")).unwrap_or_default();
+            partition_key.extend(key.iter().map(|b| format!("{b:02x}")));
+            let record = PutRecordsRequestEntry::builder()
+                .data(Blob::new(payload))
+                .partition_key(partition_key)
+                .build()?;
+
+            let mut batches = self.batches.lock().unwrap_or_else(|e| e.into_inner());
+            let buffered: usize = batches.values().map(|batch| batch.records.len()).sum();
+            let in_flight = buffered + self.in_flight.load(Ordering::Relaxed);
+            if in_flight >= self.max_in_flight {
+                anyhow::bail!("{in_flight} records in flight, Kinesis is not keeping up");
+            }
+            let batch = batches.entry(stream_name.to_string()).or_insert_with(|| Batch {
+                records: Vec::new(),
+                started_at: Instant::now(),
+            });
+            if batch.records.is_empty() {
+                batch.started_at = Instant::now();
+            }
+            batch.records.push(record);
+            if batch.records.len() >= self.batch_size {
+                self.put_batch(stream_name, batch);
+            }
+            Ok(())
+        }
+
+        fn poll(&self) {
+            let mut batches = self.batches.lock().unwrap_or_else(|e| e.into_inner());
+            for (stream_name, batch) in batches.iter_mut() {
+                if batch.started_at.elapsed() >= self.linger {
+                    self.put_batch(stream_name, batch);
+                }
+            }
+        }
+
+        fn flush(&self, timeout: Duration) -> anyhow::Result<()> {
+            for (stream_name, batch) in self.batches.lock().unwrap_or_else(|e| e.into_inner()).iter_mut() {
+                self.put_batch(stream_name, batch);
+            }
+            let deadline = Instant::now() + timeout;
+            while self.in_flight.load(Ordering::Relaxed) > 0 {
+                if Instant::now() >= deadline {
+                    anyhow::bail!("timed out waiting for PutRecords");
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            Ok(())
+        }
+
+        fn in_flight(&self) -> Option<usize> {
+            let buffered: usize = self
+                .batches
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .values()
+                .map(|batch| batch.records.len())
+                .sum();
+            Some(buffered + self.in_flight.load(Ordering::Relaxed))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// A sink whose every send hangs, like one stuck on a dead broker.
+    struct StuckSink;
+
+    impl EventSink for StuckSink {
+        fn name(&self) -> &str {
+            "stuck"
+        }
+
+        fn send(&self, _topic: &str, _key: &[u8], _payload: &[u8], _headers: &[(&str, &[u8])]) -> anyhow::Result<()> {
+            std::thread::sleep(Duration::from_secs(30));
+            Ok(())
+        }
+
+        fn poll(&self) {}
+
+        fn flush(&self, _timeout: Duration) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn in_flight(&self) -> Option<usize> {
+            None
+        }
+    }
+
+    #[test]
+    fn dropping_threaded_sink_does_not_wait_for_stuck_worker() {
+        let sink = ThreadedSink::spawn(Box::new(StuckSink), 10, Duration::ZERO, None).unwrap();
+        for _ in 0..5 {
+            sink.send("topic", b"key", b"payload", &[]).unwrap();
+        }
+        let started = Instant::now();
+        drop(sink);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
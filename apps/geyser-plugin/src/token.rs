@@ -0,0 +1,163 @@
+use crate::programs::{SPL_TOKEN, SPL_TOKEN_2022};
+use crate::transaction::TxView;
+use solana_sdk::message::compiled_instruction::CompiledInstruction;
+
+const TRANSFER: u8 = 3;
+const TRANSFER_CHECKED: u8 = 12;
+
+/// An SPL Token `Transfer` / `TransferChecked` instruction.
+pub(crate) struct TokenTransfer {
+    pub source: String,
+    pub destination: String,
+    /// Taken from the instruction for `TransferChecked`, otherwise from the
+    /// source account's token balance in the status meta.
+    pub mint: Option<String>,
+    pub amount: u64,
+    pub authority: String,
+}
+
+/// Decodes `ix` as a token transfer. Returns `None` for any other program or
+/// instruction, and for malformed data or account lists.
+pub(crate) fn decode_transfer(tx: &TxView<'_>, ix: &CompiledInstruction) -> Option<TokenTransfer> {
+    let program_id = tx.account_keys.get(usize::from(ix.program_id_index))?;
+    if *program_id != SPL_TOKEN && *program_id != SPL_TOKEN_2022 {
+        return None;
+    }
+
+    let key = |position: usize| -> Option<(u8, String)> {
+        let index = *ix.accounts.get(position)?;
+        Some((index, tx.account_keys.get(usize::from(index))?.to_string()))
+    };
+    let amount = || Some(u64::from_le_bytes(ix.data.get(1..9)?.try_into().ok()?));
+
+    match *ix.data.first()? {
+        TRANSFER => {
+            let (source_index, source) = key(0)?;
+            Some(TokenTransfer {
+                source,
+                destination: key(1)?.1,
+                mint: token_account_mint(tx, source_index),
+                amount: amount()?,
+                authority: key(2)?.1,
+            })
+        }
+        TRANSFER_CHECKED => Some(TokenTransfer {
+            source: key(0)?.1,
+            mint: Some(key(1)?.1),
+            destination: key(2)?.1,
+            amount: amount()?,
+            authority: key(3)?.1,
+        }),
+        _ => None,
+    }
+}
+
+fn token_account_mint(tx: &TxView<'_>, account_index: u8) -> Option<String> {
+    tx.meta
+        .pre_token_balances
+        .as_deref()?
+        .iter()
+        .find(|balance| balance.account_index == account_index)
+        .map(|balance| balance.mint.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::tests::with_view;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_transaction_status::TransactionStatusMeta;
+
+    const AMOUNT: u64 = 0x0102_0304_0506_0708;
+
+    /// Keys 0..=3 are plain accounts, 4 is the token program.
+    fn keys(program_id: Pubkey) -> Vec<Pubkey> {
+        let mut keys: Vec<_> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        keys.push(program_id);
+        keys
+    }
+
+    fn data(tag: u8, amount: u64) -> Vec<u8> {
+        let mut data = vec![tag];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data
+    }
+
+    fn decode(keys: &[Pubkey], accounts: Vec<u8>, data: Vec<u8>) -> Option<TokenTransfer> {
+        let ix = CompiledInstruction::new_from_raw_parts(4, data, accounts);
+        with_view(
+            keys.to_vec(),
+            vec![ix.clone()],
+            &TransactionStatusMeta::default(),
+            |tx| decode_transfer(tx, &ix),
+        )
+    }
+
+    #[test]
+    fn decodes_transfer() {
+        let keys = keys(SPL_TOKEN);
+        let transfer = decode(&keys, vec![0, 1, 2], data(TRANSFER, AMOUNT)).unwrap();
+        assert_eq!(transfer.source, keys[0].to_string());
+        assert_eq!(transfer.destination, keys[1].to_string());
+        assert_eq!(transfer.authority, keys[2].to_string());
+        assert_eq!(transfer.amount, AMOUNT);
+        // No token balances in the meta to take the mint from.
+        assert_eq!(transfer.mint, None);
+    }
+
+    #[test]
+    fn decodes_transfer_checked_with_mint_from_accounts() {
+        let keys = keys(SPL_TOKEN_2022);
+        let mut data = data(TRANSFER_CHECKED, AMOUNT);
+        data.push(6); // decimals
+        let transfer = decode(&keys, vec![0, 1, 2, 3], data).unwrap();
+        assert_eq!(transfer.source, keys[0].to_string());
+        assert_eq!(transfer.mint, Some(keys[1].to_string()));
+        assert_eq!(transfer.destination, keys[2].to_string());
+        assert_eq!(transfer.authority, keys[3].to_string());
+        assert_eq!(transfer.amount, AMOUNT);
+    }
+
+    #[test]
+    fn ignores_other_programs_and_instructions() {
+        assert!(
+            decode(
+                &keys(Pubkey::new_unique()),
+                vec![0, 1, 2],
+                data(TRANSFER, AMOUNT)
+            )
+            .is_none()
+        );
+        // Tag 7 is MintTo.
+        assert!(decode(&keys(SPL_TOKEN), vec![0, 1, 2], data(7, AMOUNT)).is_none());
+    }
+
+    #[test]
+    fn rejects_short_or_empty_data() {
+        let keys = keys(SPL_TOKEN);
+        assert!(decode(&keys, vec![0, 1, 2], vec![]).is_none());
+        assert!(decode(&keys, vec![0, 1, 2], vec![TRANSFER]).is_none());
+        let mut short = data(TRANSFER, AMOUNT);
+        short.pop();
+        assert!(decode(&keys, vec![0, 1, 2], short).is_none());
+    }
+
+    #[test]
+    fn rejects_missing_or_out_of_range_accounts() {
+        let keys = keys(SPL_TOKEN);
+        assert!(decode(&keys, vec![0, 1], data(TRANSFER, AMOUNT)).is_none());
+        assert!(decode(&keys, vec![0, 1, 2], data(TRANSFER_CHECKED, AMOUNT)).is_none());
+        assert!(decode(&keys, vec![0, 9, 2], data(TRANSFER, AMOUNT)).is_none());
+        assert!(decode(&keys, vec![0, 1, 2, 200], data(TRANSFER_CHECKED, AMOUNT)).is_none());
+    }
+
+    #[test]
+    fn rejects_out_of_range_program_index() {
+        let keys = keys(SPL_TOKEN);
+        let ix = CompiledInstruction::new_from_raw_parts(9, data(TRANSFER, AMOUNT), vec![0, 1, 2]);
+        let transfer = with_view(keys, vec![], &TransactionStatusMeta::default(), |tx| {
+            decode_transfer(tx, &ix)
+        });
+        assert!(transfer.is_none());
+    }
+}
@@ -0,0 +1,131 @@
+use crate::config::{PluginConfig, TopicSettings};
+use agave_geyser_plugin_interface::geyser_plugin_interface::{
+    GeyserPluginError,
+    Result as GeyserResult,
+};
+use log::{info, warn};
+use rdkafka::admin::{AdminClient, AdminOptions, NewTopic, TopicReplication};
+use rdkafka::client::DefaultClientContext;
+use rdkafka::config::ClientConfig;
+use rdkafka::types::RDKafkaErrorCode;
+use std::time::Duration;
+
+const ADMIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Creates `topics`, given as (plugin topic, topic on this cluster) pairs,
+/// that don't exist yet on the cluster `client_config` connects to. Settings
+/// come from `topic_settings` under the plugin's topic name, so they apply to
+/// renamed topics too. Topics that already exist are left untouched.
+///
+/// Errors retrying cannot fix (bad settings, missing permissions) are
+/// `ConfigFileReadError`; see `is_transient`.
+pub(crate) fn create_missing_topics(
+    config: &PluginConfig,
+    client_config: &ClientConfig,
+    topics: &[(&str, &str)],
+) -> GeyserResult<()> {
+    let admin: AdminClient<DefaultClientContext> =
+        client_config.create().map_err(|e| GeyserPluginError::ConfigFileReadError {
+            msg: format!("invalid Kafka admin client config: {e}"),
+        })?;
+
+    let default_settings = TopicSettings::default();
+    let settings: Vec<(&str, &TopicSettings)> = topics
+        .iter()
+        .map(|(logical, physical)| (*physical, config.topic_settings.get(*logical).unwrap_or(&default_settings)))
+        .collect();
+
+    match admin.inner().fetch_metadata(None, ADMIN_TIMEOUT) {
+        Ok(metadata) => {
+            let brokers = metadata.brokers().len();
+            for (topic, s) in &settings {
+                if s.replication_factor < 1 || s.replication_factor as usize > brokers {
+                    return Err(GeyserPluginError::ConfigFileReadError {
+                        msg: format!(
+                            "topic {topic}: replication_factor {} must be between 1 and the broker count ({brokers})",
+                            s.replication_factor
+                        ),
+                    });
+                }
+            }
+        }
+        Err(e) => warn!(
+            "RaywatchGeyserPlugin: could not fetch cluster metadata to validate replication factors: {e}"
+        ),
+    }
+
+    let retention: Vec<(Option<String>, Option<String>)> = settings
+        .iter()
+        .map(|(_, s)| {
+            (
+                s.retention_ms.map(|v| v.to_string()),
+                s.retention_bytes.map(|v| v.to_string()),
+            )
+        })
+        .collect();
+
+    let new_topics: Vec<NewTopic<'_>> = settings
+        .iter()
+        .zip(&retention)
+        .map(|((topic, s), (retention_ms, retention_bytes))| {
+            let mut new_topic =
+                NewTopic::new(topic, s.partitions, TopicReplication::Fixed(s.replication_factor));
+            if let Some(ms) = retention_ms {
+                new_topic = new_topic.set("retention.ms", ms);
+            }
+            if let Some(bytes) = retention_bytes {
+                new_topic = new_topic.set("retention.bytes", bytes);
+            }
+            new_topic
+        })
+        .collect();
+
+    let opts = AdminOptions::new().operation_timeout(Some(ADMIN_TIMEOUT));
+    let results = futures::executor::block_on(admin.create_topics(&new_topics, &opts))
+        .map_err(|e| GeyserPluginError::Custom(Box::new(e)))?;
+
+    for (result, ((_, s), new_topic)) in results.into_iter().zip(settings.iter().zip(&new_topics)) {
+        match result {
+            Ok(topic) => info!(
+                "RaywatchGeyserPlugin: created topic {topic} (partitions={}, replication_factor={}, config={:?})",
+                s.partitions, s.replication_factor, new_topic.config
+            ),
+            Err((topic, RDKafkaErrorCode::TopicAlreadyExists)) => {
+                info!("RaywatchGeyserPlugin: topic {topic} already exists");
+            }
+            Err((topic, code)) if is_permanent(code) => {
+                return Err(GeyserPluginError::ConfigFileReadError {
+                    msg: format!("failed to create topic {topic}: {code}"),
+                });
+            }
+            Err((topic, code)) => {
+                return Err(GeyserPluginError::Custom(
+                    format!("failed to create topic {topic}: {code}").into(),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a `create_missing_topics` error may go away on retry (broker
+/// unavailable, timeouts) rather than needing a config change.
+pub(crate) fn is_transient(error: &GeyserPluginError) -> bool {
+    !matches!(error, GeyserPluginError::ConfigFileReadError { .. })
+}
+
+/// Topic creation errors caused by the request itself or by permissions.
+fn is_permanent(code: RDKafkaErrorCode) -> bool {
+    matches!(
+        code,
+        RDKafkaErrorCode::InvalidTopic
+            | RDKafkaErrorCode::InvalidPartitions
+            | RDKafkaErrorCode::InvalidReplicationFactor
+            | RDKafkaErrorCode::InvalidReplicaAssignment
+            | RDKafkaErrorCode::InvalidConfig
+            | RDKafkaErrorCode::PolicyViolation
+            | RDKafkaErrorCode::TopicAuthorizationFailed
+            | RDKafkaErrorCode::ClusterAuthorizationFailed
+    )
+}
@@ -0,0 +1,373 @@
+use agave_geyser_plugin_interface::geyser_plugin_interface::ReplicaTransactionInfoVersions;
+use solana_sdk::message::compiled_instruction::CompiledInstruction;
+use solana_sdk::hash::Hash;
+use solana_sdk::message::{AccountKeys, SanitizedMessage, VersionedMessage};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::TransactionStatusMeta;
+use std::collections::HashSet;
+
+use crate::programs::{COMPUTE_BUDGET, SPL_MEMO, SPL_MEMO_V1};
+
+/// ComputeBudget `SetComputeUnitPrice { micro_lamports: u64 }`.
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Cheap vote check straight off the notification, for the hot path before any
+/// view construction, logging or allocation happens.
+pub(crate) fn is_vote(tx: &ReplicaTransactionInfoVersions<'_>) -> bool {
+    match tx {
+        ReplicaTransactionInfoVersions::V0_0_1(info) => info.is_vote,
+        ReplicaTransactionInfoVersions::V0_0_2(info) => info.is_vote,
+        ReplicaTransactionInfoVersions::V0_0_3(info) => info.is_vote,
+    }
+}
+
+/// Version-independent view over the parts of a transaction notification we read.
+///
+/// `account_keys` always includes addresses loaded from lookup tables, so
+/// instruction `program_id_index` / account indexes resolve against it directly.
+pub(crate) struct TxView<'a> {
+    pub signature: &'a Signature,
+    pub is_vote: bool,
+    /// Position of the transaction within its block; V0_0_1 does not carry it.
+    pub index: Option<usize>,
+    pub account_keys: AccountKeys<'a>,
+    pub instructions: &'a [CompiledInstruction],
+    pub recent_blockhash: &'a Hash,
+    pub meta: &'a TransactionStatusMeta,
+    message: Message<'a>,
+}
+
+/// The underlying message, for the few lookups that differ per version.
+enum Message<'a> {
+    Sanitized(&'a SanitizedMessage),
+    Versioned(&'a VersionedMessage),
+}
+
+impl<'a> TxView<'a> {
+    pub fn new(tx: &ReplicaTransactionInfoVersions<'a>) -> Self {
+        match *tx {
+            ReplicaTransactionInfoVersions::V0_0_1(info) => Self {
+                signature: info.signature,
+                is_vote: info.is_vote,
+                index: None,
+                account_keys: info.transaction.message().account_keys(),
+                instructions: info.transaction.message().instructions(),
+                recent_blockhash: info.transaction.message().recent_blockhash(),
+                meta: info.transaction_status_meta,
+                message: Message::Sanitized(info.transaction.message()),
+            },
+            ReplicaTransactionInfoVersions::V0_0_2(info) => Self {
+                signature: info.signature,
+                is_vote: info.is_vote,
+                index: Some(info.index),
+                account_keys: info.transaction.message().account_keys(),
+                instructions: info.transaction.message().instructions(),
+                recent_blockhash: info.transaction.message().recent_blockhash(),
+                meta: info.transaction_status_meta,
+                message: Message::Sanitized(info.transaction.message()),
+            },
+            ReplicaTransactionInfoVersions::V0_0_3(info) => Self {
+                signature: info.signature,
+                is_vote: info.is_vote,
+                index: Some(info.index),
+                // Versioned messages only carry static keys; lookup-table
+                // addresses come back resolved in the status meta.
+                account_keys: AccountKeys::new(
+                    info.transaction.message.static_account_keys(),
+                    Some(&info.transaction_status_meta.loaded_addresses),
+                ),
+                instructions: info.transaction.message.instructions(),
+                recent_blockhash: info.transaction.message.recent_blockhash(),
+                meta: info.transaction_status_meta,
+                message: Message::Versioned(&info.transaction.message),
+            },
+        }
+    }
+
+    /// Whether the account at `index` in `account_keys` is write-locked.
+    pub fn is_writable(&self, index: usize) -> bool {
+        match self.message {
+            Message::Sanitized(message) => message.is_writable(index),
+            Message::Versioned(message) => message.is_maybe_writable(index, None),
+        }
+    }
+
+    /// Signatures the message requires, i.e. the transaction's signer count.
+    pub fn signature_count(&self) -> u8 {
+        match self.message {
+            Message::Sanitized(message) => message.header().num_required_signatures,
+            Message::Versioned(message) => message.header().num_required_signatures,
+        }
+    }
+
+    /// `(account, pre, post)` lamport balances of write-locked accounts, in
+    /// account-key order, at most `max_len` of them.
+    pub fn lamport_balances(&self, max_len: usize) -> Vec<(&'a Pubkey, u64, u64)> {
+        let balances = self.meta.pre_balances.iter().zip(&self.meta.post_balances);
+        self.account_keys
+            .iter()
+            .zip(balances)
+            .enumerate()
+            .filter(|(index, _)| self.is_writable(*index))
+            .take(max_len)
+            .map(|(_, (account, (pre, post)))| (account, *pre, *post))
+            .collect()
+    }
+
+    /// Program log messages, each capped at `max_chars` when set. The flag is
+    /// true when any message was cut.
+    pub fn logs(&self, max_chars: Option<usize>) -> (Vec<String>, bool) {
+        let mut logs = self.meta.log_messages.clone().unwrap_or_default();
+        let mut truncated = false;
+        if let Some(max_chars) = max_chars {
+            for line in &mut logs {
+                truncated |= truncate_with_marker(line, max_chars);
+            }
+        }
+        (logs, truncated)
+    }
+
+    /// Every invoked instruction in execution order: each top-level
+    /// instruction followed by the inner (CPI) instructions it triggered.
+    pub fn invoked_instructions(&self) -> impl Iterator<Item = &'a CompiledInstruction> + '_ {
+        let inner = self.meta.inner_instructions.as_deref().unwrap_or_default();
+        self.instructions.iter().enumerate().flat_map(move |(ix_index, ix)| {
+            let inner_ixs = inner
+                .iter()
+                .filter(move |set| usize::from(set.index) == ix_index)
+                .flat_map(|set| set.instructions.iter().map(|inner_ix| &inner_ix.instruction));
+            std::iter::once(ix).chain(inner_ixs)
+        })
+    }
+
+    /// Program ids in invocation order, truncated to `max_len`.
+    pub fn program_sequence(&self, max_len: usize) -> Vec<String> {
+        self.invoked_instructions()
+            .take(max_len)
+            .map(|ix| {
+                self.account_keys
+                    .get(usize::from(ix.program_id_index))
+                    .map(|key| key.to_string())
+                    .unwrap_or_else(|| "unknown".to_string())
+            })
+            .collect()
+    }
+
+    /// Priority fee in micro-lamports per compute unit, from the last
+    /// top-level `SetComputeUnitPrice` (the runtime ignores CPI'd ones).
+    pub fn compute_unit_price(&self) -> Option<u64> {
+        self.instructions
+            .iter()
+            .rev()
+            .filter(|ix| self.account_keys.get(usize::from(ix.program_id_index)) == Some(&COMPUTE_BUDGET))
+            .find_map(|ix| match ix.data.split_first()? {
+                (&SET_COMPUTE_UNIT_PRICE, rest) => Some(u64::from_le_bytes(rest.get(..8)?.try_into().ok()?)),
+                _ => None,
+            })
+    }
+
+    /// Text of every SPL Memo instruction, joined with "; " (lossy UTF-8) and
+    /// capped at `max_chars`; `None` when there is no memo.
+    pub fn memo(&self, max_chars: usize) -> Option<String> {
+        let memos: Vec<_> = self
+            .invoked_instructions()
+            .filter(|ix| {
+                self.account_keys
+                    .get(usize::from(ix.program_id_index))
+                    .is_some_and(|program_id| *program_id == SPL_MEMO || *program_id == SPL_MEMO_V1)
+            })
+            .map(|ix| String::from_utf8_lossy(&ix.data))
+            .collect();
+        if memos.is_empty() {
+            return None;
+        }
+        let mut memo = memos.join("; ");
+        truncate_with_marker(&mut memo, max_chars);
+        Some(memo)
+    }
+
+    /// Whether any top-level or inner instruction invokes one of `programs`.
+    pub fn invokes_any(&self, programs: &HashSet<Pubkey>) -> bool {
+        self.invoked_instructions().any(|ix| {
+            self.account_keys
+                .get(usize::from(ix.program_id_index))
+                .is_some_and(|program_id| programs.contains(program_id))
+        })
+    }
+}
+
+const TRUNCATED_MARKER: &str = "...truncated";
+
+/// Truncates `s` to at most `max_chars` characters (on a char boundary),
+/// appending a marker. Returns whether anything was cut.
+pub(crate) fn truncate_with_marker(s: &mut String, max_chars: usize) -> bool {
+    match s.char_indices().nth(max_chars) {
+        Some((byte_idx, _)) => {
+            s.truncate(byte_idx);
+            s.push_str(TRUNCATED_MARKER);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use agave_geyser_plugin_interface::geyser_plugin_interface::ReplicaTransactionInfoV3;
+    use solana_sdk::message::legacy;
+    use solana_sdk::transaction::VersionedTransaction;
+    use solana_transaction_status::{InnerInstruction, InnerInstructions};
+
+    /// Runs `f` against a view of a legacy transaction with the given keys and
+    /// top-level instructions.
+    pub(crate) fn with_view<R>(
+        account_keys: Vec<Pubkey>,
+        instructions: Vec<CompiledInstruction>,
+        meta: &TransactionStatusMeta,
+        f: impl FnOnce(&TxView<'_>) -> R,
+    ) -> R {
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::Legacy(legacy::Message {
+                account_keys,
+                instructions,
+                ..legacy::Message::default()
+            }),
+        };
+        let info = ReplicaTransactionInfoV3 {
+            signature: &transaction.signatures[0],
+            message_hash: &Hash::default(),
+            is_vote: false,
+            transaction: &transaction,
+            transaction_status_meta: meta,
+            index: 0,
+        };
+        f(&TxView::new(&ReplicaTransactionInfoVersions::V0_0_3(&info)))
+    }
+
+    /// Key 0 is the payer, 1 the ComputeBudget program, 2 some other program.
+    fn keys() -> Vec<Pubkey> {
+        vec![Pubkey::new_unique(), COMPUTE_BUDGET, Pubkey::new_unique()]
+    }
+
+    fn ix(program_id_index: u8, data: Vec<u8>) -> CompiledInstruction {
+        CompiledInstruction::new_from_raw_parts(program_id_index, data, vec![])
+    }
+
+    fn set_price(micro_lamports: u64) -> Vec<u8> {
+        let mut data = vec![SET_COMPUTE_UNIT_PRICE];
+        data.extend_from_slice(&micro_lamports.to_le_bytes());
+        data
+    }
+
+    fn compute_unit_price(instructions: Vec<CompiledInstruction>, meta: &TransactionStatusMeta) -> Option<u64> {
+        with_view(keys(), instructions, meta, |tx| tx.compute_unit_price())
+    }
+
+    #[test]
+    fn compute_unit_price_reads_set_compute_unit_price() {
+        let meta = TransactionStatusMeta::default();
+        // SetComputeUnitLimit (tag 2) alongside the price.
+        let limit = ix(1, vec![2, 0x40, 0x0d, 0x03, 0x00]);
+        assert_eq!(compute_unit_price(vec![limit, ix(1, set_price(5_000))], &meta), Some(5_000));
+    }
+
+    #[test]
+    fn compute_unit_price_takes_the_last_top_level_price() {
+        let meta = TransactionStatusMeta::default();
+        let instructions = vec![ix(1, set_price(1)), ix(2, vec![]), ix(1, set_price(2))];
+        assert_eq!(compute_unit_price(instructions, &meta), Some(2));
+    }
+
+    #[test]
+    fn compute_unit_price_ignores_other_compute_budget_instructions() {
+        let meta = TransactionStatusMeta::default();
+        // RequestHeapFrame and SetComputeUnitLimit.
+        let instructions = vec![ix(1, vec![1, 0, 0, 1, 0]), ix(1, vec![2, 0x40, 0x0d, 0x03, 0x00])];
+        assert_eq!(compute_unit_price(instructions, &meta), None);
+        assert_eq!(compute_unit_price(vec![ix(1, vec![])], &meta), None);
+    }
+
+    #[test]
+    fn compute_unit_price_ignores_other_programs() {
+        let meta = TransactionStatusMeta::default();
+        assert_eq!(compute_unit_price(vec![ix(2, set_price(7))], &meta), None);
+        assert_eq!(compute_unit_price(vec![ix(9, set_price(7))], &meta), None);
+    }
+
+    #[test]
+    fn compute_unit_price_rejects_truncated_data() {
+        let meta = TransactionStatusMeta::default();
+        let mut truncated = set_price(7);
+        truncated.pop();
+        assert_eq!(compute_unit_price(vec![ix(1, truncated)], &meta), None);
+        assert_eq!(compute_unit_price(vec![ix(1, vec![SET_COMPUTE_UNIT_PRICE])], &meta), None);
+    }
+
+    #[test]
+    fn compute_unit_price_ignores_inner_instructions() {
+        let meta = TransactionStatusMeta {
+            inner_instructions: Some(vec![InnerInstructions {
+                index: 0,
+                instructions: vec![InnerInstruction {
+                    instruction: ix(1, set_price(7)),
+                    stack_height: Some(2),
+                }],
+            }]),
+            ..TransactionStatusMeta::default()
+        };
+        assert_eq!(compute_unit_price(vec![ix(2, vec![])], &meta), None);
+    }
+
+    /// Memo views use key 1 for the v3 memo program, 2 for v1, 3 for another program.
+    fn memo(instructions: Vec<CompiledInstruction>, meta: &TransactionStatusMeta, max_chars: usize) -> Option<String> {
+        let keys = vec![Pubkey::new_unique(), SPL_MEMO, SPL_MEMO_V1, Pubkey::new_unique()];
+        with_view(keys, instructions, meta, |tx| tx.memo(max_chars))
+    }
+
+    #[test]
+    fn memo_reads_both_memo_programs() {
+        let meta = TransactionStatusMeta::default();
+        assert_eq!(memo(vec![ix(1, b"v3".to_vec())], &meta, 64).as_deref(), Some("v3"));
+        assert_eq!(memo(vec![ix(2, b"v1".to_vec())], &meta, 64).as_deref(), Some("v1"));
+    }
+
+    #[test]
+    fn memo_is_none_without_memo_instructions() {
+        let meta = TransactionStatusMeta::default();
+        assert_eq!(memo(vec![], &meta, 64), None);
+        assert_eq!(memo(vec![ix(3, b"not a memo".to_vec())], &meta, 64), None);
+    }
+
+    #[test]
+    fn memo_joins_memos_in_invocation_order() {
+        let meta = TransactionStatusMeta {
+            inner_instructions: Some(vec![InnerInstructions {
+                index: 1,
+                instructions: vec![InnerInstruction {
+                    instruction: ix(1, b"inner".to_vec()),
+                    stack_height: Some(2),
+                }],
+            }]),
+            ..TransactionStatusMeta::default()
+        };
+        let instructions = vec![ix(2, b"first".to_vec()), ix(3, vec![]), ix(1, b"last".to_vec())];
+        assert_eq!(memo(instructions, &meta, 64).as_deref(), Some("first; inner; last"));
+    }
+
+    #[test]
+    fn memo_replaces_invalid_utf8() {
+        let meta = TransactionStatusMeta::default();
+        let text = memo(vec![ix(1, vec![b'o', b'k', 0xff, 0xfe])], &meta, 64);
+        assert_eq!(text.as_deref(), Some("ok\u{fffd}\u{fffd}"));
+    }
+
+    #[test]
+    fn memo_is_capped_at_max_chars() {
+        let meta = TransactionStatusMeta::default();
+        let instructions = vec![ix(1, b"abc".to_vec()), ix(2, b"def".to_vec())];
+        assert_eq!(memo(instructions, &meta, 4).as_deref(), Some("abc;...truncated"));
+    }
+}
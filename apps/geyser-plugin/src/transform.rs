@@ -0,0 +1,139 @@
+//! Optional pre-send transform hook backed by a sandboxed WASM module.
+//!
+//! Guest ABI (no imports are provided, so the module cannot reach the host):
+//! - `memory`: the exported linear memory.
+//! - `alloc(len: i32) -> i32`: returns a guest pointer with room for `len`
+//!   bytes; the host copies the serialized event there.
+//! - `transform(ptr: i32, len: i32) -> i64`: returns the output location
+//!   packed as `(out_ptr << 32) | out_len`, or any negative value to drop the
+//!   event. The guest owns its memory and should reuse buffers between calls.
+//!
+//! Each call runs with a fuel budget, so a runaway module traps instead of
+//! stalling the notification thread. After a failed call (a trap, fuel
+//! exhaustion or an out-of-bounds result) the guest is instantiated afresh,
+//! since its memory may be left inconsistent.
+//!
+//! Requires the `wasm-transform` feature.
+
+#[cfg(feature = "wasm-transform")]
+pub(crate) use self::wasm::WasmTransform;
+
+pub(crate) enum Transformed {
+    Send(Vec<u8>),
+    #[cfg_attr(not(feature = "wasm-transform"), allow(dead_code))]
+    Drop,
+}
+
+/// Stand-in used when the plugin is built without `wasm-transform`.
+#[cfg(not(feature = "wasm-transform"))]
+pub(crate) struct WasmTransform;
+
+#[cfg(not(feature = "wasm-transform"))]
+impl WasmTransform {
+    pub fn load(_path: &str, _fuel: u64) -> anyhow::Result<Self> {
+        anyhow::bail!("`transform_wasm` is configured but the plugin was built without the `wasm-transform` feature")
+    }
+
+    pub fn apply(&self, payload: &[u8]) -> anyhow::Result<Transformed> {
+        Ok(Transformed::Send(payload.to_vec()))
+    }
+}
+
+#[cfg(feature = "wasm-transform")]
+mod wasm {
+    use super::Transformed;
+    use log::error;
+    use std::sync::Mutex;
+    use wasmtime::{
+        Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+        TypedFunc,
+    };
+
+    /// Upper bound on the guest's linear memory.
+    const MAX_MEMORY_BYTES: usize = 64 << 20;
+
+    struct Guest {
+        store: Store<StoreLimits>,
+        memory: Memory,
+        alloc: TypedFunc<i32, i32>,
+        transform: TypedFunc<(i32, i32), i64>,
+    }
+
+    impl Guest {
+        fn instantiate(module: &Module) -> anyhow::Result<Self> {
+            let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY_BYTES).build();
+            let mut store = Store::new(module.engine(), limits);
+            store.limiter(|limits| limits);
+
+            let instance: Instance = Linker::new(module.engine()).instantiate(&mut store, module)?;
+            let memory = instance
+                .get_memory(&mut store, "memory")
+                .ok_or_else(|| anyhow::anyhow!("module does not export `memory`"))?;
+            let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+            let transform = instance.get_typed_func::<(i32, i32), i64>(&mut store, "transform")?;
+            Ok(Self {
+                store,
+                memory,
+                alloc,
+                transform,
+            })
+        }
+
+        fn call(&mut self, payload: &[u8], fuel: u64) -> anyhow::Result<Transformed> {
+            let store = &mut self.store;
+            store.set_fuel(fuel)?;
+            let len = i32::try_from(payload.len())?;
+            let ptr = self.alloc.call(&mut *store, len)?;
+            // Guest pointers are unsigned 32-bit offsets.
+            self.memory.write(&mut *store, ptr as u32 as usize, payload)?;
+
+            let packed = self.transform.call(&mut *store, (ptr, len))?;
+            if packed < 0 {
+                return Ok(Transformed::Drop);
+            }
+
+            // Both halves are below 2^32, so the sum cannot overflow.
+            let out_ptr = (packed >> 32) as usize;
+            let out_len = (packed & 0xffff_ffff) as usize;
+            let out = self
+                .memory
+                .data(&*store)
+                .get(out_ptr..out_ptr + out_len)
+                .ok_or_else(|| anyhow::anyhow!("transform output {out_ptr}+{out_len} is outside guest memory"))?;
+            Ok(Transformed::Send(out.to_vec()))
+        }
+    }
+
+    pub(crate) struct WasmTransform {
+        fuel: u64,
+        module: Module,
+        guest: Mutex<Guest>,
+    }
+
+    impl WasmTransform {
+        pub fn load(path: &str, fuel: u64) -> anyhow::Result<Self> {
+            let mut config = Config::new();
+            config.consume_fuel(true);
+            let engine = Engine::new(&config)?;
+            let module = Module::from_file(&engine, path)?;
+            let guest = Guest::instantiate(&module)?;
+            Ok(Self {
+                fuel,
+                module,
+                guest: Mutex::new(guest),
+            })
+        }
+
+        pub fn apply(&self, payload: &[u8]) -> anyhow::Result<Transformed> {
+            let mut guest = self.guest.lock().unwrap_or_else(|e| e.into_inner());
+            let result = guest.call(payload, self.fuel);
+            if result.is_err() {
+                match Guest::instantiate(&self.module) {
+                    Ok(fresh) => *guest = fresh,
+                    Err(e) => error!("RaywatchGeyserPlugin: failed to re-instantiate the transform module: {e}"),
+                }
+            }
+            result
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use log::info;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+const UNSET: u64 = u64::MAX;
+
+/// Suppresses emission for the first `slots` slots seen after load, so a
+/// plugin started mid-stream from a snapshot begins on a clean slot boundary.
+pub(crate) struct Warmup {
+    slots: u64,
+    first_slot: AtomicU64,
+    done: AtomicBool,
+    suppressed: AtomicU64,
+}
+
+impl Warmup {
+    pub fn new(slots: u64) -> Self {
+        Self {
+            slots,
+            first_slot: AtomicU64::new(UNSET),
+            done: AtomicBool::new(slots == 0),
+            suppressed: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns whether an event for `slot` may be emitted, counting it as
+    /// suppressed otherwise.
+    pub fn allow(&self, slot: u64) -> bool {
+        if self.done.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        let first_slot = match self
+            .first_slot
+            .compare_exchange(UNSET, slot, Ordering::Relaxed, Ordering::Relaxed)
+        {
+            Ok(_) => slot,
+            Err(existing) => existing,
+        };
+
+        if slot < first_slot.saturating_add(self.slots) {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+
+        if !self.done.swap(true, Ordering::Relaxed) {
+            info!(
+                "RaywatchGeyserPlugin: warmup complete after {} slots (first seen slot {first_slot}, {} events suppressed); first emitted slot {slot}",
+                self.slots,
+                self.suppressed.load(Ordering::Relaxed)
+            );
+        }
+        true
+    }
+}